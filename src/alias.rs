@@ -18,7 +18,8 @@ pub fn create_alias(
         .ok_or_else(|| std::io::ErrorKind::from(std::io::ErrorKind::NotFound))?;
     let alias_dir = aliases_dir.join(common_name);
 
-    if alias_dir.exists() {
+    // A dangling alias doesn't `exist()`, but it still has to be replaced
+    if std::fs::symlink_metadata(&alias_dir).is_ok() {
         remove_symlink_dir(&alias_dir)?;
     }
 
@@ -46,7 +47,7 @@ impl std::convert::TryInto<StroredAlias> for &std::path::Path {
 
     fn try_into(self) -> Result<StroredAlias, Self::Error> {
         let shallow_self = shallow_read_symlink(self)?;
-        let destination_path = if shallow_self == system_version::path() {
+        let destination_path = if shallow_self == system_version::legacy_path() {
             shallow_self
         } else {
             std::fs::canonicalize(&shallow_self)?
@@ -60,12 +61,10 @@ impl std::convert::TryInto<StroredAlias> for &std::path::Path {
 
 impl StroredAlias {
     pub fn s_ver(&self) -> &str {
-        if self.destination_path == system_version::path() {
+        if self.destination_path == system_version::legacy_path() {
             system_version::display_name()
         } else {
             self.destination_path
-                .parent()
-                .unwrap()
                 .file_name()
                 .expect("must have basename")
                 .to_str()
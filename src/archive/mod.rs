@@ -1,3 +1,5 @@
 pub mod extract;
+pub mod tar_bz2;
+pub mod tar_gz;
 pub mod tar_xz;
 pub mod zip;
\ No newline at end of file
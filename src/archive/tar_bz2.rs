@@ -0,0 +1,22 @@
+use crate::archive::extract::{Error, Extract};
+use std::io::Read;
+use std::path::Path;
+
+pub struct TarBz2<R: Read> {
+    response: R,
+}
+
+impl<R: Read> TarBz2<R> {
+    pub fn new(response: R) -> Self {
+        Self { response }
+    }
+}
+
+impl<R: Read> Extract for TarBz2<R> {
+    fn extract_into<P: AsRef<Path>>(self, path: P) -> Result<(), Error> {
+        let bz2_stream = bzip2::read::BzDecoder::new(self.response);
+        let mut tar_archive = tar::Archive::new(bz2_stream);
+        tar_archive.unpack(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use crate::config::MamimiConfig;
+use crate::python_version::PythonVersion;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Command;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Can't parse {path:?}: {source}")]
+    ParseError {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+}
+
+/// Compiler and build settings, all optional so they can be layered.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct BuildSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cflags: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ldflags: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub configure_opts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub make_opts: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub make_jobs: Option<usize>,
+}
+
+impl BuildSettings {
+    /// Layers `other` on top of `self`: scalars are replaced, options are appended.
+    pub fn merge(mut self, other: &Self) -> Self {
+        if other.cc.is_some() {
+            self.cc = other.cc.clone();
+        }
+        if other.cflags.is_some() {
+            self.cflags = other.cflags.clone();
+        }
+        if other.ldflags.is_some() {
+            self.ldflags = other.ldflags.clone();
+        }
+        if other.make_jobs.is_some() {
+            self.make_jobs = other.make_jobs;
+        }
+        self.configure_opts
+            .extend(other.configure_opts.iter().cloned());
+        self.make_opts.extend(other.make_opts.iter().cloned());
+        self
+    }
+
+    pub fn make_jobs(&self) -> usize {
+        self.make_jobs.unwrap_or_else(num_cpus::get)
+    }
+
+    /// Exports `CC`, `CFLAGS` and `LDFLAGS` to a `configure` or `make` invocation.
+    pub fn apply_env(&self, command: &mut Command) {
+        let vars = [
+            ("CC", &self.cc),
+            ("CFLAGS", &self.cflags),
+            ("LDFLAGS", &self.ldflags),
+        ];
+        for (name, value) in vars {
+            if let Some(value) = value {
+                command.env(name, value);
+            }
+        }
+    }
+}
+
+/// The contents of `build.toml` in the base directory:
+///
+/// ```toml
+/// [global]
+/// make_jobs = 4
+///
+/// [versions."3.8.*"]
+/// cflags = "-O2 -fno-semantic-interposition"
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct BuildConfig {
+    #[serde(default)]
+    pub global: BuildSettings,
+    #[serde(default)]
+    pub versions: BTreeMap<String, BuildSettings>,
+}
+
+impl BuildConfig {
+    pub fn path(config: &MamimiConfig) -> PathBuf {
+        config.base_dir_with_default().join("build.toml")
+    }
+
+    pub fn load(config: &MamimiConfig) -> Result<Self, Error> {
+        let path = Self::path(config);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                toml::from_str(&contents).map_err(|source| Error::ParseError { path, source })
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// The global settings overlaid with every matching version pattern,
+    /// less specific patterns first.
+    pub fn settings_for(&self, version: &PythonVersion) -> BuildSettings {
+        let version = version.to_string();
+        let mut patterns = self
+            .versions
+            .iter()
+            .filter(|(pattern, _)| matches_pattern(pattern, &version))
+            .collect::<Vec<_>>();
+        patterns.sort_by_key(|(pattern, _)| pattern.trim_end_matches('*').len());
+        patterns
+            .into_iter()
+            .fold(self.global.clone(), |settings, (_, overrides)| {
+                settings.merge(overrides)
+            })
+    }
+}
+
+/// Matches `3.8.*`-style patterns where `*` stands for any run of characters.
+fn matches_pattern(pattern: &str, version: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match version.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let (last, middle) = match parts.split_last() {
+        Some(split) => split,
+        None => return rest.is_empty(),
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(v: &str) -> PythonVersion {
+        PythonVersion::parse(v).unwrap()
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("3.8.*", "3.8.12"));
+        assert!(matches_pattern("3.*.1", "3.10.1"));
+        assert!(matches_pattern("3.9.6", "3.9.6"));
+        assert!(!matches_pattern("3.8.*", "3.9.1"));
+        assert!(!matches_pattern("3.9", "3.9.6"));
+    }
+
+    #[test]
+    fn test_settings_for_layers_patterns() {
+        let config: BuildConfig = toml::from_str(
+            r#"
+            [global]
+            cflags = "-O2"
+            make_jobs = 2
+            configure_opts = ["--enable-shared"]
+
+            [versions."3.*"]
+            cc = "gcc"
+
+            [versions."3.8.*"]
+            cflags = "-O3"
+            configure_opts = ["--without-ensurepip"]
+            "#,
+        )
+        .unwrap();
+        let settings = config.settings_for(&version("3.8.12"));
+        assert_eq!(settings.cc.as_deref(), Some("gcc"));
+        assert_eq!(settings.cflags.as_deref(), Some("-O3"));
+        assert_eq!(settings.make_jobs(), 2);
+        assert_eq!(
+            settings.configure_opts,
+            vec!["--enable-shared", "--without-ensurepip"]
+        );
+        let settings = config.settings_for(&version("3.10.1"));
+        assert_eq!(settings.cflags.as_deref(), Some("-O2"));
+        assert_eq!(settings.configure_opts, vec!["--enable-shared"]);
+    }
+
+    #[test]
+    fn test_load_reports_unknown_keys() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        assert_eq!(
+            BuildConfig::load(&config).unwrap().global,
+            BuildSettings::default()
+        );
+
+        std::fs::write(BuildConfig::path(&config), "[global]\ncflag = \"-O2\"\n").unwrap();
+        assert!(matches!(
+            BuildConfig::load(&config),
+            Err(Error::ParseError { .. })
+        ));
+    }
+}
@@ -0,0 +1,349 @@
+use crate::executable::find_in_path;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// The package managers we know the package names of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Apt,
+    Dnf,
+    Apk,
+    Pacman,
+    Brew,
+}
+
+impl PackageManager {
+    /// Detects the package manager from `/etc/os-release` on Linux.
+    pub fn detect() -> Option<Self> {
+        if cfg!(target_os = "macos") {
+            return Some(Self::Brew);
+        }
+        let os_release = std::fs::read_to_string("/etc/os-release").ok()?;
+        Self::from_os_release(&os_release)
+    }
+
+    fn from_os_release(os_release: &str) -> Option<Self> {
+        let ids = os_release
+            .lines()
+            .filter_map(|line| {
+                line.strip_prefix("ID=")
+                    .or_else(|| line.strip_prefix("ID_LIKE="))
+            })
+            .flat_map(|value| value.trim_matches('"').split_whitespace())
+            .collect::<Vec<_>>();
+        ids.iter().find_map(|id| match *id {
+            "debian" | "ubuntu" => Some(Self::Apt),
+            "fedora" | "rhel" | "centos" => Some(Self::Dnf),
+            "alpine" => Some(Self::Apk),
+            "arch" => Some(Self::Pacman),
+            _ => None,
+        })
+    }
+
+    pub fn install_command(self, packages: &[&str]) -> String {
+        let command = match self {
+            Self::Apt => "sudo apt-get install -y",
+            Self::Dnf => "sudo dnf install -y",
+            Self::Apk => "sudo apk add",
+            Self::Pacman => "sudo pacman -S --needed",
+            Self::Brew => "brew install",
+        };
+        format!("{} {}", command, packages.join(" "))
+    }
+}
+
+const COMPILERS: &[&str] = &["cc", "gcc", "clang"];
+
+/// Package names per package manager, in the order of [`PackageManager`].
+/// An empty name means the system already ships it.
+type Packages = [&'static str; 5];
+
+#[derive(Debug)]
+pub enum Probe {
+    /// An executable on `PATH`, any of the listed names.
+    Executable(&'static [&'static str]),
+    /// A library found with pkg-config or a header that compiles.
+    Library {
+        pkg_config: &'static str,
+        header: &'static str,
+    },
+    /// OpenSSL as `configure` gets pointed at it, see [`crate::openssl::detect`],
+    /// or else found like a [`Probe::Library`].
+    OpenSsl {
+        pkg_config: &'static str,
+        header: &'static str,
+    },
+}
+
+#[derive(Debug)]
+pub struct Dependency {
+    pub name: &'static str,
+    /// Whether CPython is unusable without it, e.g. pip needs `ssl` and `zlib`.
+    pub required: bool,
+    pub probe: Probe,
+    packages: Packages,
+}
+
+impl Dependency {
+    pub fn package(&self, package_manager: PackageManager) -> &'static str {
+        self.packages[package_manager as usize]
+    }
+}
+
+pub const DEPENDENCIES: &[Dependency] = &[
+    Dependency {
+        name: "C compiler",
+        required: true,
+        probe: Probe::Executable(COMPILERS),
+        packages: ["build-essential", "gcc", "build-base", "base-devel", ""],
+    },
+    Dependency {
+        name: "make",
+        required: true,
+        probe: Probe::Executable(&["make"]),
+        packages: ["make", "make", "make", "make", "make"],
+    },
+    Dependency {
+        name: "openssl",
+        required: true,
+        probe: Probe::OpenSsl {
+            pkg_config: "openssl",
+            header: "openssl/ssl.h",
+        },
+        packages: [
+            "libssl-dev",
+            "openssl-devel",
+            "openssl-dev",
+            "openssl",
+            "openssl@3",
+        ],
+    },
+    Dependency {
+        name: "zlib",
+        required: true,
+        probe: Probe::Library {
+            pkg_config: "zlib",
+            header: "zlib.h",
+        },
+        packages: ["zlib1g-dev", "zlib-devel", "zlib-dev", "zlib", ""],
+    },
+    Dependency {
+        name: "libffi",
+        required: true,
+        probe: Probe::Library {
+            pkg_config: "libffi",
+            header: "ffi.h",
+        },
+        packages: ["libffi-dev", "libffi-devel", "libffi-dev", "libffi", ""],
+    },
+    Dependency {
+        name: "bzip2",
+        required: false,
+        probe: Probe::Library {
+            pkg_config: "bzip2",
+            header: "bzlib.h",
+        },
+        packages: ["libbz2-dev", "bzip2-devel", "bzip2-dev", "bzip2", ""],
+    },
+    Dependency {
+        name: "lzma",
+        required: false,
+        probe: Probe::Library {
+            pkg_config: "liblzma",
+            header: "lzma.h",
+        },
+        packages: ["liblzma-dev", "xz-devel", "xz-dev", "xz", "xz"],
+    },
+    Dependency {
+        name: "sqlite3",
+        required: false,
+        probe: Probe::Library {
+            pkg_config: "sqlite3",
+            header: "sqlite3.h",
+        },
+        packages: ["libsqlite3-dev", "sqlite-devel", "sqlite-dev", "sqlite", ""],
+    },
+    Dependency {
+        name: "readline",
+        required: false,
+        probe: Probe::Library {
+            pkg_config: "readline",
+            header: "readline/readline.h",
+        },
+        packages: [
+            "libreadline-dev",
+            "readline-devel",
+            "readline-dev",
+            "readline",
+            "readline",
+        ],
+    },
+];
+
+#[derive(Debug)]
+pub struct Preflight {
+    pub missing: Vec<&'static Dependency>,
+    pub package_manager: Option<PackageManager>,
+}
+
+impl Preflight {
+    pub fn missing_required(&self) -> impl Iterator<Item = &&'static Dependency> {
+        self.missing.iter().filter(|dependency| dependency.required)
+    }
+
+    pub fn missing_optional(&self) -> impl Iterator<Item = &&'static Dependency> {
+        self.missing
+            .iter()
+            .filter(|dependency| !dependency.required)
+    }
+
+    /// The command installing every missing dependency, when the distribution is known.
+    pub fn install_hint(&self) -> Option<String> {
+        let package_manager = self.package_manager?;
+        let mut packages = self
+            .missing
+            .iter()
+            .map(|dependency| dependency.package(package_manager))
+            .filter(|package| !package.is_empty())
+            .collect::<Vec<_>>();
+        packages.dedup();
+        if packages.is_empty() {
+            return None;
+        }
+        Some(package_manager.install_command(&packages))
+    }
+}
+
+impl std::fmt::Display for Preflight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = |dependencies: Vec<&&Dependency>| {
+            dependencies
+                .iter()
+                .map(|dependency| dependency.name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let required = self.missing_required().collect::<Vec<_>>();
+        let optional = self.missing_optional().collect::<Vec<_>>();
+        if !required.is_empty() {
+            writeln!(
+                f,
+                "missing required build dependencies: {}",
+                names(required)
+            )?;
+        }
+        if !optional.is_empty() {
+            writeln!(
+                f,
+                "missing optional build dependencies: {}",
+                names(optional)
+            )?;
+        }
+        match self.install_hint() {
+            Some(hint) => write!(f, "install them with: {}", hint),
+            None => write!(f, "install them with your system's package manager"),
+        }
+    }
+}
+
+/// Checks for the tools and libraries needed to build CPython from source.
+pub fn preflight() -> Preflight {
+    let compiler = COMPILERS.iter().find_map(find_in_path);
+    let has_pkg_config = find_in_path("pkg-config").is_some();
+    let library_exists = |pkg_config: &str, header: &str| {
+        has_pkg_config && pkg_config_exists(pkg_config)
+            || compiler
+                .as_ref()
+                .map_or(false, |compiler| header_compiles(compiler, header))
+    };
+    let missing = DEPENDENCIES
+        .iter()
+        .filter(|dependency| match dependency.probe {
+            Probe::Executable(names) => names.iter().all(|name| find_in_path(name).is_none()),
+            Probe::Library { pkg_config, header } => !library_exists(pkg_config, header),
+            // `build_package` passes a keg-only or `OPENSSL_DIR` install to configure
+            Probe::OpenSsl { pkg_config, header } => {
+                crate::openssl::detect().is_none() && !library_exists(pkg_config, header)
+            }
+        })
+        .collect();
+    Preflight {
+        missing,
+        package_manager: PackageManager::detect(),
+    }
+}
+
+fn pkg_config_exists(name: &str) -> bool {
+    Command::new("pkg-config")
+        .arg("--exists")
+        .arg(name)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Preprocesses `#include <header>` with the user's `CPPFLAGS`, the same way `configure` would.
+fn header_compiles(compiler: &std::path::Path, header: &str) -> bool {
+    let cppflags = std::env::var("CPPFLAGS").unwrap_or_default();
+    let child = Command::new(compiler)
+        .args(cppflags.split_whitespace())
+        .args(["-E", "-x", "c", "-", "-o"])
+        .arg(if cfg!(windows) { "NUL" } else { "/dev/null" })
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(_) => return false,
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        if writeln!(stdin, "#include <{}>", header).is_err() {
+            return false;
+        }
+    }
+    child.wait().map(|status| status.success()).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_manager_from_os_release() {
+        let ubuntu = "NAME=\"Ubuntu\"\nID=ubuntu\nID_LIKE=debian\n";
+        assert_eq!(
+            PackageManager::from_os_release(ubuntu),
+            Some(PackageManager::Apt)
+        );
+        let rocky = "ID=\"rocky\"\nID_LIKE=\"rhel centos fedora\"\n";
+        assert_eq!(
+            PackageManager::from_os_release(rocky),
+            Some(PackageManager::Dnf)
+        );
+        assert_eq!(
+            PackageManager::from_os_release("ID=alpine\n"),
+            Some(PackageManager::Apk)
+        );
+        assert_eq!(PackageManager::from_os_release("ID=plan9\n"), None);
+    }
+
+    #[test]
+    fn test_install_hint() {
+        let openssl = DEPENDENCIES.iter().find(|x| x.name == "openssl").unwrap();
+        let zlib = DEPENDENCIES.iter().find(|x| x.name == "zlib").unwrap();
+        let preflight = Preflight {
+            missing: vec![openssl, zlib],
+            package_manager: Some(PackageManager::Apt),
+        };
+        assert_eq!(
+            preflight.install_hint().as_deref(),
+            Some("sudo apt-get install -y libssl-dev zlib1g-dev")
+        );
+        let preflight = Preflight {
+            missing: vec![zlib],
+            package_manager: Some(PackageManager::Brew),
+        };
+        assert_eq!(preflight.install_hint(), None);
+    }
+}
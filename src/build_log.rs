@@ -0,0 +1,246 @@
+use crate::config::MamimiConfig;
+use crate::path_ext::PathExt;
+use crate::python_version::PythonVersion;
+use std::fs::File;
+use std::io::{BufRead, BufReader, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+
+/// How many lines of the log are shown when a build step fails.
+pub const TAIL_LINES: usize = 30;
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+pub fn logs_dir(config: &MamimiConfig) -> PathBuf {
+    config
+        .base_dir_with_default()
+        .join("logs")
+        .ensure_exists_silently()
+}
+
+/// The output of every step of one build, in `logs/<version>-<timestamp>.log`.
+pub struct BuildLog {
+    path: PathBuf,
+    file: Arc<Mutex<File>>,
+}
+
+impl BuildLog {
+    pub fn create(config: &MamimiConfig, version: &PythonVersion) -> std::io::Result<Self> {
+        let timestamp = chrono::Local::now().format(TIMESTAMP_FORMAT);
+        let path = logs_dir(config).join(format!("{}-{}.log", version, timestamp));
+        let file = File::create(&path)?;
+        Ok(Self {
+            path,
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Runs `command`, appending its stdout and stderr to the log. Every line
+    /// is passed to `on_line` as well, and the combined output is returned.
+    pub fn run(
+        &self,
+        step: &str,
+        command: &mut Command,
+        on_line: &(dyn Fn(&str) + Sync),
+    ) -> std::io::Result<(ExitStatus, String)> {
+        writeln!(self.file.lock().unwrap(), "==> {}: {:?}", step, command)?;
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+        let output = Mutex::new(String::new());
+        std::thread::scope(|scope| {
+            let readers = [
+                scope.spawn(|| self.copy_lines(stdout, &output, on_line)),
+                scope.spawn(|| self.copy_lines(stderr, &output, on_line)),
+            ];
+            readers
+                .into_iter()
+                .try_for_each(|reader| reader.join().expect("log reader panicked"))
+        })?;
+        let status = child.wait()?;
+        writeln!(self.file.lock().unwrap(), "==> {}: {}", step, status)?;
+        Ok((status, output.into_inner().unwrap()))
+    }
+
+    fn copy_lines(
+        &self,
+        stream: impl Read,
+        output: &Mutex<String>,
+        on_line: &(dyn Fn(&str) + Sync),
+    ) -> std::io::Result<()> {
+        // Compilers may print non-UTF-8 bytes, e.g. in paths or localized messages
+        let mut reader = BufReader::new(stream);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            if reader.read_until(b'\n', &mut buf)? == 0 {
+                break;
+            }
+            let bytes = buf.strip_suffix(b"\n").unwrap_or(&buf);
+            let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+            let line = String::from_utf8_lossy(bytes);
+            writeln!(self.file.lock().unwrap(), "{}", line)?;
+            let mut output = output.lock().unwrap();
+            output.push_str(&line);
+            output.push('\n');
+            on_line(&line);
+        }
+        Ok(())
+    }
+
+    pub fn tail(&self, lines: usize) -> String {
+        tail(&self.path, lines)
+    }
+}
+
+pub fn tail(path: &Path, lines: usize) -> String {
+    let contents = std::fs::read_to_string(path).unwrap_or_default();
+    let all = contents.lines().collect::<Vec<_>>();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
+
+/// A single status line on stderr that is rewritten for every compiled file.
+pub struct Progress {
+    enabled: bool,
+    prefix: String,
+    count: Mutex<usize>,
+}
+
+impl Progress {
+    pub fn new(config: &MamimiConfig, prefix: impl Into<String>) -> Self {
+        Self {
+            enabled: std::io::stderr().is_terminal()
+                && config
+                    .log_level()
+                    .is_writable(&crate::log_level::LogLevel::Error),
+            prefix: prefix.into(),
+            count: Mutex::new(0),
+        }
+    }
+
+    /// Keeps the status line hidden unless `enabled`, e.g. while other builds
+    /// write to the same terminal.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled &= enabled;
+        self
+    }
+
+    pub fn update(&self, line: &str) {
+        if !self.enabled {
+            return;
+        }
+        let mut count = self.count.lock().unwrap();
+        *count += 1;
+        let target = compiled_file(line).unwrap_or("");
+        let mut stderr = std::io::stderr();
+        let _ = write!(
+            stderr,
+            "\r\x1b[2K{} [{} lines] {}",
+            self.prefix, count, target
+        );
+        let _ = stderr.flush();
+    }
+
+    pub fn finish(&self) {
+        if self.enabled {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+/// The source file of a compiler invocation, e.g. `Objects/listobject.c`.
+fn compiled_file(line: &str) -> Option<&str> {
+    line.split_whitespace()
+        .rev()
+        .find(|word| word.ends_with(".c") || word.ends_with(".o"))
+}
+
+/// Every log of `version`, oldest first.
+pub fn find_logs(config: &MamimiConfig, version: &PythonVersion) -> std::io::Result<Vec<PathBuf>> {
+    let prefix = format!("{}-", version);
+    let mut logs = std::fs::read_dir(logs_dir(config))?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&prefix))
+                .and_then(|rest| rest.strip_suffix(".log"))
+                .map_or(false, |timestamp| {
+                    chrono::NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).is_ok()
+                })
+        })
+        .collect::<Vec<_>>();
+    logs.sort();
+    Ok(logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_writes_both_streams() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let version = PythonVersion::parse("3.12.4-debug").unwrap();
+        let log = BuildLog::create(&config, &version).unwrap();
+        let seen = Mutex::new(0);
+        let (status, output) = log
+            .run(
+                "make",
+                Command::new("sh").args(["-c", "echo out; echo err >&2; exit 3"]),
+                &|_| *seen.lock().unwrap() += 1,
+            )
+            .unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert!(output.contains("out\n") && output.contains("err\n"));
+        assert_eq!(*seen.lock().unwrap(), 2);
+        assert!(log.tail(1).contains("exit status: 3"));
+
+        assert_eq!(
+            find_logs(&config, &version).unwrap(),
+            vec![log.path().to_path_buf()]
+        );
+        let other = PythonVersion::parse("3.12.4").unwrap();
+        assert!(find_logs(&config, &other).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_keeps_reading_after_non_utf8_output() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let version = PythonVersion::parse("3.12.4").unwrap();
+        let log = BuildLog::create(&config, &version).unwrap();
+        let (status, output) = log
+            .run(
+                "make",
+                Command::new("sh").args(["-c", r"printf 'caf\351\n'; echo done"]),
+                &|_| {},
+            )
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(output, "caf\u{FFFD}\ndone\n");
+        assert!(log.tail(2).starts_with("done"));
+    }
+
+    #[test]
+    fn test_compiled_file() {
+        assert_eq!(
+            compiled_file("gcc -c -O3 -o Objects/listobject.o Objects/listobject.c"),
+            Some("Objects/listobject.c")
+        );
+        assert_eq!(compiled_file("checking for gcc... gcc"), None);
+    }
+}
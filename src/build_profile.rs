@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A preset of `configure` options. Installations built with a profile live
+/// next to the plain build, e.g. `3.12.4-debug`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuildProfile {
+    Optimized,
+    Debug,
+    FreeThreaded,
+}
+
+impl BuildProfile {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Optimized => "optimized",
+            Self::Debug => "debug",
+            Self::FreeThreaded => "free-threaded",
+        }
+    }
+
+    pub fn configure_opts(self) -> &'static [&'static str] {
+        match self {
+            Self::Optimized => &["--enable-optimizations", "--with-lto"],
+            Self::Debug => &["--with-pydebug"],
+            Self::FreeThreaded => &["--disable-gil"],
+        }
+    }
+}
+
+impl std::fmt::Display for BuildProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for BuildProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "optimized" => Ok(Self::Optimized),
+            "debug" => Ok(Self::Debug),
+            "free-threaded" => Ok(Self::FreeThreaded),
+            _ => Err(format!(
+                "Invalid profile: {}. Expected one of: optimized, debug, free-threaded",
+                s
+            )),
+        }
+    }
+}
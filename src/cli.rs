@@ -36,6 +36,30 @@ pub enum SubCommand {
     /// Print shell completions to stdout
     #[clap(name = "completions", bin_name = "completions")]
     Completions(commands::completions::Completions),
+    /// Checks the environment and the installations for common problems
+    #[clap(name = "doctor", bin_name = "doctor")]
+    Doctor(commands::doctor::Doctor),
+    /// Prints the latest build log of a version
+    #[clap(name = "logs", bin_name = "logs")]
+    Logs(commands::logs::Logs),
+    /// Moves a series such as 3.11 to its latest patch release
+    #[clap(name = "upgrade", bin_name = "upgrade")]
+    Upgrade(commands::upgrade::Upgrade),
+    /// Lists installed versions with newer patches or an ending support
+    #[clap(name = "outdated", bin_name = "outdated")]
+    Outdated(commands::outdated::Outdated),
+    /// Removes superseded patch versions that nothing uses anymore
+    #[clap(name = "prune", bin_name = "prune")]
+    Prune(commands::prune::Prune),
+    /// Registers an interpreter installed elsewhere as a version
+    #[clap(name = "link", bin_name = "link")]
+    Link(commands::link::Link),
+    /// Removes a version registered with `link`, leaving the interpreter alone
+    #[clap(name = "unlink", bin_name = "unlink")]
+    Unlink(commands::unlink::Unlink),
+    /// Takes over the versions installed by pyenv, asdf or uv
+    #[clap(name = "import", bin_name = "import")]
+    Import(commands::import::Import),
 }
 
 impl SubCommand {
@@ -48,6 +72,14 @@ impl SubCommand {
             Self::Local(cmd) => cmd.call(&config),
             Self::Global(cmd) => cmd.call(&config),
             Self::Completions(cmd) => cmd.call(&config),
+            Self::Doctor(cmd) => cmd.call(&config),
+            Self::Logs(cmd) => cmd.call(&config),
+            Self::Upgrade(cmd) => cmd.call(&config),
+            Self::Outdated(cmd) => cmd.call(&config),
+            Self::Prune(cmd) => cmd.call(&config),
+            Self::Link(cmd) => cmd.call(&config),
+            Self::Unlink(cmd) => cmd.call(&config),
+            Self::Import(cmd) => cmd.call(&config),
         }
     }
 }
@@ -1,35 +1,227 @@
+use crate::alias::list_aliases;
 use crate::cli::Cli;
 use crate::commands::command::Command;
 use crate::config::MamimiConfig;
 use crate::shell::{infer_shell, AVAILABLE_SHELLS};
-use clap::{IntoApp, Parser};
-use clap_complete::{Generator, Shell};
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
+use indoc::formatdoc;
+use std::io::Write;
+use std::str::FromStr;
 use thiserror::Error;
 
+/// Subcommands whose version argument is completed by calling back into
+/// `mamimi completions --list`, paired with the lists they complete from.
+const DYNAMIC_ARGUMENTS: &[(&str, &str)] = &[
+    ("local", "--list installed --list aliases"),
+    ("global", "--list installed --list aliases"),
+    ("uninstall", "--list installed"),
+    ("install", "--list remote"),
+];
+
 #[derive(Parser, Debug)]
 pub struct Completions {
     /// The shell syntax to use. Infers when missing.
     #[clap(long)]
     shell: Option<Shell>,
+    /// Print candidates for a version argument instead of a completion script.
+    #[clap(long, hide = true)]
+    list: Vec<CompletionList>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum CompletionList {
+    Installed,
+    Aliases,
+    Remote,
+}
+
+impl FromStr for CompletionList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "installed" => Ok(Self::Installed),
+            "aliases" => Ok(Self::Aliases),
+            "remote" => Ok(Self::Remote),
+            _ => Err(format!(
+                "Invalid list: {}. Expected one of: installed, aliases, remote",
+                s
+            )),
+        }
+    }
 }
 
 impl Command for Completions {
     type Error = Error;
 
-    fn apply(self, _config: &MamimiConfig) -> Result<(), Self::Error> {
+    fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
         let mut stdio = std::io::stdout();
-        let shell = self
-            .shell
-            .or_else(|| infer_shell().map(Into::into))
-            .ok_or(Error::CantInferShell)?;
-        let app = Cli::command();
-        shell.generate(&app, &mut stdio);
+        if !self.list.is_empty() {
+            for candidate in list_candidates(config, &self.list)? {
+                writeln!(stdio, "{}", candidate)?;
+            }
+            return Ok(());
+        }
+        let shell = match self.shell {
+            Some(shell) => shell,
+            None => infer_shell()
+                .ok_or(Error::CantInferShell)?
+                .to_clap_shell()
+                .map_err(Error::UnsupportedShell)?,
+        };
+        let mut script = vec![];
+        clap_complete::generate(shell, &mut Cli::command(), "mamimi", &mut script);
+        let script = String::from_utf8_lossy(&script);
+        write!(stdio, "{}", with_dynamic_completions(shell, &script))?;
         Ok(())
     }
 }
 
+fn list_candidates(config: &MamimiConfig, lists: &[CompletionList]) -> Result<Vec<String>, Error> {
+    let mut candidates = vec![];
+    for list in lists {
+        match list {
+            CompletionList::Installed => {
+                for entry in config.versions_dir().read_dir()? {
+                    let entry = entry?;
+                    if crate::python_version::is_dotfile(&entry) {
+                        continue;
+                    }
+                    if let Some(name) = entry.file_name().to_str() {
+                        candidates.push(name.to_string());
+                    }
+                }
+            }
+            CompletionList::Aliases => {
+                for alias in list_aliases(config)? {
+                    candidates.push(alias.name().to_string());
+                }
+            }
+            CompletionList::Remote => {
+                for version in crate::remote_python_index::read_cache(config).iter().rev() {
+                    candidates.push(version.to_string());
+                }
+            }
+        }
+    }
+    Ok(candidates)
+}
+
+/// Appends a wrapper around the static clap completions that completes the
+/// version argument of [`DYNAMIC_ARGUMENTS`] from the current installation.
+fn with_dynamic_completions(shell: Shell, script: &str) -> String {
+    let cases = |case: &dyn Fn(&str, &str) -> String| {
+        DYNAMIC_ARGUMENTS
+            .iter()
+            .map(|(subcommand, lists)| case(subcommand, lists))
+            .collect::<String>()
+    };
+    match shell {
+        Shell::Bash => formatdoc!(
+            r#"
+                {script}
+
+                _mamimi_dynamic() {{
+                    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+                    if [[ $COMP_CWORD -eq 2 && $cur != -* ]]; then
+                        case "${{COMP_WORDS[1]}}" in
+                {cases}        esac
+                    fi
+                    _mamimi "$@"
+                }}
+                complete -F _mamimi_dynamic -o bashdefault -o default mamimi
+            "#,
+            script = script.trim_end(),
+            cases = cases(&|subcommand, lists| format!(
+                "            {}) COMPREPLY=($(compgen -W \"$(mamimi completions {} 2>/dev/null)\" \
+                 -- \"$cur\")); return 0 ;;\n",
+                subcommand, lists
+            )),
+        ),
+        Shell::Zsh => {
+            // clap registers `_mamimi` at the end of the script; register the wrapper instead.
+            let script = match script.rfind("if [ \"$funcstack[1]\" = \"_mamimi\" ]") {
+                Some(index) => &script[..index],
+                None => script,
+            };
+            formatdoc!(
+                r#"
+                    {script}
+
+                    _mamimi_dynamic() {{
+                        if (( CURRENT == 3 )) && [[ $words[CURRENT] != -* ]]; then
+                            case $words[2] in
+                    {cases}        esac
+                        fi
+                        _mamimi "$@"
+                    }}
+
+                    if [ "$funcstack[1]" = "_mamimi" ]; then
+                        _mamimi_dynamic "$@"
+                    else
+                        compdef _mamimi_dynamic mamimi
+                    fi
+                "#,
+                script = script.trim_end(),
+                cases = cases(&|subcommand, lists| format!(
+                    "            {}) compadd -- \
+                     ${{(f)\"$(mamimi completions {} 2>/dev/null)\"}}; return ;;\n",
+                    subcommand, lists
+                )),
+            )
+        }
+        Shell::Fish => format!(
+            "{}\n{}",
+            script.trim_end(),
+            cases(&|subcommand, lists| format!(
+                "complete -c mamimi -n \"__fish_seen_subcommand_from {}\" \
+                 -f -a \"(mamimi completions {} 2>/dev/null)\"\n",
+                subcommand, lists
+            ))
+        ),
+        Shell::PowerShell => {
+            // Keep clap's completer around and only answer the version argument ourselves.
+            let script = script.replace(
+                "Register-ArgumentCompleter -Native -CommandName 'mamimi' -ScriptBlock {",
+                "$__mamimiStaticCompleter = {",
+            );
+            formatdoc!(
+                r#"
+                    {script}
+
+                    Register-ArgumentCompleter -Native -CommandName 'mamimi' -ScriptBlock {{
+                        param($wordToComplete, $commandAst, $cursorPosition)
+
+                        $elements = @($commandAst.CommandElements | ForEach-Object {{ $_.ToString() }})
+                        $position = if ($wordToComplete) {{ $elements.Count - 1 }} else {{ $elements.Count }}
+                        if ($position -eq 2 -and -not $wordToComplete.StartsWith('-')) {{
+                            $candidates = $null
+                            switch ($elements[1]) {{
+                    {cases}        }}
+                            if ($null -ne $candidates) {{
+                                return $candidates.Where{{ $_ -like "$wordToComplete*" }} |
+                                    ForEach-Object {{ [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_) }}
+                            }}
+                        }}
+                        & $__mamimiStaticCompleter $wordToComplete $commandAst $cursorPosition
+                    }}
+                "#,
+                script = script.trim_end(),
+                cases = cases(&|subcommand, lists| format!(
+                    "            '{}' {{ $candidates = @(mamimi completions {} 2>$null) }}\n",
+                    subcommand, lists
+                )),
+            )
+        }
+        _ => script.to_string(),
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum Error {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
     #[error(
         "{}\n{}\n{}\n{}",
         "Can't infer shell!",
@@ -38,6 +230,8 @@ pub enum Error {
         shells_as_string()
     )]
     CantInferShell,
+    #[error("{0}")]
+    UnsupportedShell(anyhow::Error),
 }
 
 fn shells_as_string() -> String {
@@ -47,3 +241,47 @@ fn shells_as_string() -> String {
         .collect::<Vec<_>>()
         .join("\n")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_candidates() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        std::fs::create_dir_all(config.versions_dir().join("3.9.6")).unwrap();
+        std::fs::create_dir_all(config.versions_dir().join(".downloads")).unwrap();
+
+        let candidates = list_candidates(&config, &[CompletionList::Installed]).unwrap();
+        assert_eq!(candidates, vec!["3.9.6".to_string()]);
+        assert!(list_candidates(&config, &[CompletionList::Remote])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_dynamic_completions_for_every_shell() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut script = vec![];
+            clap_complete::generate(shell, &mut Cli::command(), "mamimi", &mut script);
+            let script = with_dynamic_completions(shell, &String::from_utf8_lossy(&script));
+            assert!(
+                script.contains("mamimi completions --list installed --list aliases"),
+                "{}",
+                shell
+            );
+            assert!(
+                script.contains("mamimi completions --list remote"),
+                "{}",
+                shell
+            );
+        }
+    }
+
+    #[test]
+    fn test_unsupported_shell_is_an_error() {
+        let shell: Box<dyn crate::shell::Shell> = Box::from(crate::shell::WindowsCommand);
+        assert!(shell.to_clap_shell().is_err());
+    }
+}
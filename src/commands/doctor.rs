@@ -0,0 +1,367 @@
+use crate::config::MamimiConfig;
+use crate::executable::is_executable;
+use crate::input_version::InputVersion;
+use crate::python_version::PythonVersion;
+use crate::version_files::get_user_version_for_directory;
+use colored::Colorize;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MamimiError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+    #[error("{count} check(s) failed")]
+    ChecksFailed { count: usize },
+}
+
+#[derive(clap::Parser, Debug, Default)]
+pub struct Doctor {
+    /// Print the results as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Pass,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+impl crate::commands::command::Command for Doctor {
+    type Error = MamimiError;
+
+    fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
+        let checks = run_checks(config);
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&checks)?);
+        } else {
+            for check in &checks {
+                let status = match check.status {
+                    Status::Pass => "pass".green(),
+                    Status::Warn => "warn".yellow(),
+                    Status::Fail => "fail".red(),
+                };
+                println!("[{}] {}: {}", status.bold(), check.name, check.message);
+                if let Some(hint) = &check.hint {
+                    println!("       {} {}", "hint:".cyan(), hint);
+                }
+            }
+        }
+        let count = checks
+            .iter()
+            .filter(|check| check.status == Status::Fail)
+            .count();
+        if count > 0 {
+            return Err(MamimiError::ChecksFailed { count });
+        }
+        Ok(())
+    }
+}
+
+pub fn run_checks(config: &MamimiConfig) -> Vec<Check> {
+    vec![
+        check_multishell_path(config),
+        check_path_order(config),
+        check_shadowing_pythons(config),
+        check_default_alias(config),
+        check_dangling_aliases(config),
+        check_leftover_downloads(config),
+        check_build_dependencies(),
+        check_version_file(config),
+    ]
+}
+
+const INIT_HINT: &str = "add `eval \"$(mamimi init)\"` to your shell profile and restart the shell";
+
+fn multishell_bin_dir(multishell_path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        multishell_path.to_path_buf()
+    } else {
+        multishell_path.join("bin")
+    }
+}
+
+fn path_entries() -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default()
+}
+
+fn check_multishell_path(config: &MamimiConfig) -> Check {
+    const NAME: &str = "multishell path";
+    match config.multishell_path() {
+        None => Check::fail(NAME, "MAMIMI_MULTISHELL_PATH is not set", INIT_HINT),
+        Some(path) if std::fs::symlink_metadata(path).is_err() => Check::fail(
+            NAME,
+            format!(
+                "MAMIMI_MULTISHELL_PATH points to {:?}, which does not exist",
+                path
+            ),
+            INIT_HINT,
+        ),
+        Some(path) if !path.is_dir() => Check::warn(
+            NAME,
+            format!("{:?} does not point to a Python version", path),
+            "run `mamimi local <version>` or `mamimi global <version>`",
+        ),
+        Some(path) => Check::pass(NAME, format!("{:?}", path)),
+    }
+}
+
+fn check_path_order(config: &MamimiConfig) -> Check {
+    const NAME: &str = "PATH order";
+    let bin_dir = match config.multishell_path() {
+        Some(path) => multishell_bin_dir(path),
+        None => return Check::fail(NAME, "mamimi is not initialized", INIT_HINT),
+    };
+    match path_entries().iter().position(|entry| entry == &bin_dir) {
+        Some(0) => Check::pass(NAME, format!("{:?} is first on PATH", bin_dir)),
+        Some(index) => Check::warn(
+            NAME,
+            format!("{:?} is entry #{} on PATH", bin_dir, index + 1),
+            "initialize mamimi at the end of your shell profile so nothing is prepended after it",
+        ),
+        None => Check::fail(NAME, format!("{:?} is not on PATH", bin_dir), INIT_HINT),
+    }
+}
+
+fn check_shadowing_pythons(config: &MamimiConfig) -> Check {
+    const NAME: &str = "shadowing pythons";
+    let entries = path_entries();
+    let bin_dir = match config.multishell_path().map(multishell_bin_dir) {
+        Some(bin_dir) if entries.contains(&bin_dir) => bin_dir,
+        _ => return Check::warn(NAME, "mamimi is not on PATH", INIT_HINT),
+    };
+    let shadowing = entries
+        .into_iter()
+        .take_while(|entry| entry != &bin_dir)
+        .flat_map(|entry| {
+            ["python", "python3"]
+                .iter()
+                .map(|name| entry.join(name))
+                .collect::<Vec<_>>()
+        })
+        .filter(|path| is_executable(path))
+        .collect::<Vec<_>>();
+    if shadowing.is_empty() {
+        Check::pass(NAME, "no python found before mamimi on PATH")
+    } else {
+        Check::fail(
+            NAME,
+            format!("found before mamimi on PATH: {:?}", shadowing),
+            "move mamimi's initialization after the tools that add these directories to PATH",
+        )
+    }
+}
+
+fn check_default_alias(config: &MamimiConfig) -> Check {
+    const NAME: &str = "default alias";
+    let default_dir = config.default_python_version_dir();
+    if std::fs::symlink_metadata(&default_dir).is_err() {
+        return Check::warn(
+            NAME,
+            "no default version is set",
+            "run `mamimi global <version>`",
+        );
+    }
+    match std::fs::canonicalize(&default_dir) {
+        Ok(target) => Check::pass(NAME, format!("points to {:?}", target)),
+        Err(_) => Check::fail(
+            NAME,
+            format!(
+                "points to {:?}, which does not exist",
+                std::fs::read_link(&default_dir).unwrap_or(default_dir)
+            ),
+            "run `mamimi global <version>` with an installed version",
+        ),
+    }
+}
+
+fn check_dangling_aliases(config: &MamimiConfig) -> Check {
+    const NAME: &str = "aliases";
+    let dangling = std::fs::read_dir(config.aliases_dir())
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| std::fs::metadata(path).is_err())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if dangling.is_empty() {
+        Check::pass(NAME, "every alias points to an installed version")
+    } else {
+        Check::warn(
+            NAME,
+            format!("dangling aliases: {:?}", dangling),
+            "remove them or point them to an installed version",
+        )
+    }
+}
+
+fn check_leftover_downloads(config: &MamimiConfig) -> Check {
+    const NAME: &str = "leftover downloads";
+    let downloads_dir = config.versions_dir().join(".downloads");
+    let leftovers = std::fs::read_dir(&downloads_dir)
+        .map(|entries| entries.filter_map(Result::ok).count())
+        .unwrap_or(0);
+    if leftovers == 0 {
+        Check::pass(NAME, "no interrupted installations")
+    } else {
+        Check::warn(
+            NAME,
+            format!("{} leftover entries in {:?}", leftovers, downloads_dir),
+            format!("remove {:?} when no installation is running", downloads_dir),
+        )
+    }
+}
+
+fn check_build_dependencies() -> Check {
+    const NAME: &str = "build dependencies";
+    if cfg!(windows) {
+        return Check::pass(NAME, "prebuilt packages are used on Windows");
+    }
+    let preflight = crate::build_deps::preflight();
+    if preflight.missing.is_empty() {
+        return Check::pass(NAME, "everything needed to build Python is available");
+    }
+    let names = preflight
+        .missing
+        .iter()
+        .map(|dependency| dependency.name)
+        .collect::<Vec<_>>()
+        .join(", ");
+    Check::warn(
+        NAME,
+        format!("missing {}", names),
+        preflight
+            .install_hint()
+            .unwrap_or_else(|| "install them with your system's package manager".to_string()),
+    )
+}
+
+fn check_version_file(config: &MamimiConfig) -> Check {
+    const NAME: &str = "version file";
+    let version = match std::env::current_dir()
+        .ok()
+        .and_then(get_user_version_for_directory)
+    {
+        Some(version) => version,
+        None => return Check::pass(NAME, "no .python-version in the current directory"),
+    };
+    if is_installed(config, &version) {
+        Check::pass(NAME, format!("requested {} is installed", version))
+    } else {
+        Check::warn(
+            NAME,
+            format!("requested {} is not installed", version),
+            format!("run `mamimi install {}`", version),
+        )
+    }
+}
+
+fn is_installed(config: &MamimiConfig, version: &InputVersion) -> bool {
+    if let InputVersion::Full(PythonVersion::System) = version {
+        return true;
+    }
+    let installed = std::fs::read_dir(config.versions_dir())
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| !crate::python_version::is_dotfile(entry))
+                .filter_map(|entry| PythonVersion::parse(entry.file_name().to_str()?).ok())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    version.to_version(&installed).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_of(checks: &[Check], name: &str) -> Status {
+        checks
+            .iter()
+            .find(|check| check.name == name)
+            .expect("Can't find check")
+            .status
+    }
+
+    #[test]
+    fn test_broken_aliases_and_leftovers() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        crate::symlink::create_symlink_dir(
+            config.versions_dir().join("3.9.6"),
+            config.default_python_version_dir(),
+        )
+        .unwrap();
+        std::fs::create_dir_all(config.versions_dir().join(".downloads").join("tmp")).unwrap();
+
+        let checks = run_checks(&config);
+        assert_eq!(status_of(&checks, "multishell path"), Status::Fail);
+        assert_eq!(status_of(&checks, "default alias"), Status::Fail);
+        assert_eq!(status_of(&checks, "aliases"), Status::Warn);
+        assert_eq!(status_of(&checks, "leftover downloads"), Status::Warn);
+
+        std::fs::create_dir_all(config.versions_dir().join("3.9.6")).unwrap();
+        std::fs::remove_dir_all(config.versions_dir().join(".downloads")).unwrap();
+        let checks = run_checks(&config);
+        assert_eq!(status_of(&checks, "default alias"), Status::Pass);
+        assert_eq!(status_of(&checks, "aliases"), Status::Pass);
+        assert_eq!(status_of(&checks, "leftover downloads"), Status::Pass);
+    }
+
+    #[test]
+    fn test_json_output() {
+        let checks = vec![Check::warn("aliases", "dangling", "remove them")];
+        let json = serde_json::to_value(&checks).unwrap();
+        assert_eq!(json[0]["status"], "warn");
+        assert_eq!(json[0]["hint"], "remove them");
+    }
+}
@@ -2,7 +2,10 @@ use super::command::Command;
 use crate::alias::create_alias;
 use crate::commands::versions;
 use crate::input_version::InputVersion;
-use crate::python_version::PythonVersion;
+use crate::lock::Lock;
+use crate::python_version::{installed_versions, PythonVersion};
+use crate::release_status::warn_if_eol;
+use crate::system_version;
 use log::debug;
 use reqwest::Version;
 use thiserror::Error;
@@ -15,6 +18,8 @@ pub enum MamimiError {
     IoError(#[from] std::io::Error),
     #[error("Requested version {version} is not currently installed")]
     VersionNotFound { version: InputVersion },
+    #[error("Can't find a Python interpreter on PATH outside of mamimi's directories")]
+    NoSystemInterpreter,
 }
 
 #[derive(clap::Parser, Debug)]
@@ -26,19 +31,29 @@ impl Command for Global {
     type Error = MamimiError;
     fn apply(self, config: &crate::config::MamimiConfig) -> Result<(), Self::Error> {
         debug!("Use {} as the default version", &self.version);
-        let version = match self.version.clone() {
-            InputVersion::Full(PythonVersion::Semver(v)) => PythonVersion::Semver(v),
-            version => return Err(MamimiError::VersionNotFound { version }),
+        let version = match &self.version {
+            InputVersion::Full(version) => version.clone(),
+            // `3.12` or `3.12-opt` means the newest one installed
+            input => input
+                .to_version(&installed_versions(config)?)
+                .cloned()
+                .ok_or_else(|| MamimiError::VersionNotFound {
+                    version: self.version.clone(),
+                })?,
         };
-        if !&config
-            .versions_dir()
-            .join(self.version.to_string())
-            .exists()
-        {
+        // Holding the version's lock keeps it from being uninstalled while it's linked
+        let _version_lock = Lock::version(config, &version)?;
+        let _aliases_lock = Lock::aliases(config)?;
+        if version == PythonVersion::System {
+            if system_version::refresh(config)?.is_none() {
+                return Err(MamimiError::NoSystemInterpreter);
+            }
+        } else if !&config.versions_dir().join(version.to_string()).exists() {
             return Err(MamimiError::VersionNotFound {
                 version: self.version.clone(),
             });
         }
+        warn_if_eol(config, &version);
         create_alias(&config, "default", &version).map_err(MamimiError::IoError)?;
         Ok(())
     }
@@ -0,0 +1,644 @@
+use crate::alias::create_alias;
+use crate::commands::link::{self, link_entry};
+use crate::config::MamimiConfig;
+use crate::install_metadata::{InstallMetadata, Origin};
+use crate::interpreter::{find_in_prefix, probe_version};
+use crate::lock::Lock;
+use crate::outln;
+use crate::python_version::PythonVersion;
+use crate::system_version;
+use colored::Colorize;
+use dirs::home_dir;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MamimiError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    LinkError(#[from] link::MamimiError),
+    #[error(
+        "Can't find the {tool} installations, looked in {path:?}. Pass --root to point at them"
+    )]
+    RootNotFound { tool: Source, path: PathBuf },
+    #[error("Can't tell where {tool} keeps its installations. Pass --root to point at them")]
+    UnknownRoot { tool: Source },
+    #[error("{version} is already installed")]
+    AlreadyInstalled { version: PythonVersion },
+    #[error("no interpreter found in {prefix:?}")]
+    NoInterpreter { prefix: PathBuf },
+    #[error("its interpreter reports {reported}")]
+    UnexpectedVersion { reported: PythonVersion },
+    #[error("{interpreter:?} doesn't run")]
+    InterpreterFailed { interpreter: PathBuf },
+    #[error("it doesn't run from the versions directory, {reason}")]
+    NotRelocatable { reason: Box<MamimiError> },
+    #[error("it still uses {path:?} from where it was. Import it with --link instead")]
+    UsesOriginal { path: PathBuf },
+}
+
+/// A version manager whose installations can be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Pyenv,
+    Asdf,
+    /// uv's managed Pythons, and the toolchains of rye which fetches the same builds.
+    Uv,
+}
+
+impl Source {
+    pub fn possible_values() -> &'static [&'static str] {
+        &["pyenv", "asdf", "uv"]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pyenv => "pyenv",
+            Self::Asdf => "asdf",
+            Self::Uv => "uv",
+        }
+    }
+
+    /// The directories holding one installation each, honoring the tool's own
+    /// environment variables.
+    fn default_roots(&self) -> Vec<PathBuf> {
+        let from_env = |var: &str| std::env::var_os(var).map(PathBuf::from);
+        let home = home_dir();
+        let roots = match self {
+            Self::Pyenv => vec![from_env("PYENV_ROOT")
+                .or_else(|| home.map(|home| home.join(".pyenv")))
+                .map(|root| root.join("versions"))],
+            Self::Asdf => vec![from_env("ASDF_DATA_DIR")
+                .or_else(|| home.map(|home| home.join(".asdf")))
+                .map(|root| root.join("installs").join("python"))],
+            Self::Uv => vec![
+                from_env("UV_PYTHON_INSTALL_DIR").or_else(|| {
+                    from_env("XDG_DATA_HOME")
+                        .or_else(|| home.clone().map(|home| home.join(".local").join("share")))
+                        .map(|data| data.join("uv").join("python"))
+                }),
+                from_env("RYE_HOME")
+                    .or_else(|| home.map(|home| home.join(".rye")))
+                    .map(|root| root.join("py")),
+            ],
+        };
+        roots.into_iter().flatten().collect()
+    }
+
+    /// The version an installation directory claims to hold, e.g. `3.12.4` for
+    /// pyenv's `3.12.4`, uv's `cpython-3.12.4-linux-x86_64-gnu` or rye's
+    /// `cpython@3.12.4`.
+    fn version_of(&self, dir_name: &str) -> Option<PythonVersion> {
+        let version = match self {
+            Self::Pyenv | Self::Asdf => dir_name,
+            Self::Uv => {
+                let rest = dir_name
+                    .strip_prefix("cpython-")
+                    .or_else(|| dir_name.strip_prefix("cpython@"))?;
+                rest.split('-').next()?
+            }
+        };
+        // Build metadata marks variants such as uv's `3.13.0+freethreaded`
+        match PythonVersion::parse(version).ok()? {
+            PythonVersion::Semver(v) if v.build.is_empty() => Some(PythonVersion::Semver(v)),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pyenv" => Ok(Self::Pyenv),
+            "asdf" => Ok(Self::Asdf),
+            "uv" | "rye" => Ok(Self::Uv),
+            _ => Err(format!(
+                "Invalid source: {}. Expected one of: {}",
+                s,
+                Self::possible_values().join(", ")
+            )),
+        }
+    }
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct Import {
+    /// The version manager to import from
+    #[clap(value_parser = Source::from_str)]
+    pub source: Source,
+    /// The directory holding its installations, when not in the usual place
+    #[clap(long, value_name = "DIR")]
+    pub root: Option<PathBuf>,
+    /// Move the installations, leaving nothing behind
+    #[clap(long = "move", conflicts_with_all = &["link", "copy"])]
+    pub move_: bool,
+    /// Link the installations in place. They break if the other tool removes them
+    #[clap(long, conflicts_with = "copy")]
+    pub link: bool,
+    /// Copy the installations, the default
+    #[clap(long)]
+    pub copy: bool,
+}
+
+/// One installation found in the other tool's directory.
+#[derive(Debug, Clone, PartialEq)]
+struct Candidate {
+    version: PythonVersion,
+    prefix: PathBuf,
+}
+
+impl crate::commands::command::Command for Import {
+    type Error = MamimiError;
+
+    fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
+        let roots = match &self.root {
+            Some(root) => vec![root.clone()],
+            None => self.source.default_roots(),
+        };
+        let existing = roots
+            .iter()
+            .filter(|root| root.is_dir())
+            .collect::<Vec<_>>();
+        if existing.is_empty() {
+            return Err(match roots.into_iter().next() {
+                Some(path) => MamimiError::RootNotFound {
+                    tool: self.source,
+                    path,
+                },
+                None => MamimiError::UnknownRoot { tool: self.source },
+            });
+        }
+        let mut candidates = vec![];
+        for root in existing {
+            candidates.extend(self.candidates(root)?);
+        }
+        candidates.sort_by(|a, b| a.version.cmp(&b.version));
+
+        let mut imported = 0;
+        for candidate in &candidates {
+            match self.import(config, candidate) {
+                Ok(()) => {
+                    imported += 1;
+                    outln!(
+                        config,
+                        Error,
+                        "{} Imported {} from {}",
+                        "==>".green(),
+                        candidate.version.to_string().green(),
+                        candidate.prefix.display()
+                    );
+                }
+                Err(reason) => outln!(
+                    config,
+                    Error,
+                    "{} Skipping {}: {}",
+                    "warning:".yellow().bold(),
+                    candidate.prefix.display(),
+                    reason
+                ),
+            }
+        }
+        outln!(
+            config,
+            Error,
+            "{} Imported {} of {} {} installation(s)",
+            "==>".green(),
+            imported,
+            candidates.len(),
+            self.source
+        );
+
+        if self.source == Source::Pyenv {
+            self.import_global_version(config)?;
+        }
+        Ok(())
+    }
+}
+
+impl Import {
+    fn candidates(&self, root: &Path) -> std::io::Result<Vec<Candidate>> {
+        let mut candidates = vec![];
+        for entry in std::fs::read_dir(root)? {
+            let entry = entry?;
+            // pyenv keeps its virtualenvs next to the versions, as symlinks
+            if entry.file_type()?.is_symlink() || !entry.path().is_dir() {
+                continue;
+            }
+            let name = entry.file_name();
+            let version = match name.to_str().and_then(|name| self.source.version_of(name)) {
+                Some(version) => version,
+                None => continue,
+            };
+            let prefix = entry.path();
+            // Older rye toolchains nest the installation one level down
+            let prefix = match prefix.join("install") {
+                install if install.is_dir() => install,
+                _ => prefix,
+            };
+            candidates.push(Candidate { version, prefix });
+        }
+        Ok(candidates)
+    }
+
+    /// Brings one installation over, explaining why when it can't.
+    fn import(&self, config: &MamimiConfig, candidate: &Candidate) -> Result<(), MamimiError> {
+        let version = &candidate.version;
+        let entry = config.versions_dir().join(version.to_string());
+        if std::fs::symlink_metadata(&entry).is_ok() {
+            return Err(MamimiError::AlreadyInstalled {
+                version: version.clone(),
+            });
+        }
+        verify(&candidate.prefix, version)?;
+        let metadata = InstallMetadata {
+            version: version.to_string(),
+            installed_at: chrono::Local::now().to_rfc3339(),
+            origin: Origin::Imported,
+            linked_to: self.link.then(|| candidate.prefix.clone()),
+            ..InstallMetadata::default()
+        };
+        if self.link {
+            let interpreter =
+                find_in_prefix(&candidate.prefix).ok_or_else(|| MamimiError::NoInterpreter {
+                    prefix: candidate.prefix.clone(),
+                })?;
+            link_entry(
+                config,
+                version,
+                &interpreter,
+                interpreter.parent(),
+                &metadata,
+            )?;
+            return Ok(());
+        }
+
+        let _lock = Lock::version(config, version)?;
+        let moved = self.move_ && std::fs::rename(&candidate.prefix, &entry).is_ok();
+        if !moved {
+            if let Err(err) = copy_tree(&candidate.prefix, &entry) {
+                let _ = std::fs::remove_dir_all(&entry);
+                return Err(err.into());
+            }
+        }
+        // Builds that aren't relocatable only show up once they have moved
+        let relocated = rewrite_shebangs(&entry.join("bin"), &candidate.prefix, &entry)
+            .map_err(MamimiError::from)
+            .and_then(|()| verify(&entry, version))
+            .map_err(|reason| MamimiError::NotRelocatable {
+                reason: Box::new(reason),
+            })
+            .and_then(|()| check_independent(&entry));
+        if let Err(err) = relocated {
+            if moved {
+                std::fs::rename(&entry, &candidate.prefix)?;
+                rewrite_shebangs(&candidate.prefix.join("bin"), &entry, &candidate.prefix)?;
+            } else {
+                std::fs::remove_dir_all(&entry)?;
+            }
+            return Err(err);
+        }
+        metadata.write(&entry)?;
+        if self.move_ && !moved {
+            // Copied across file systems, the original can go now that the copy works
+            std::fs::remove_dir_all(&candidate.prefix)?;
+        }
+        Ok(())
+    }
+
+    /// Makes pyenv's global version, if mamimi has it now, the `default` alias.
+    fn import_global_version(&self, config: &MamimiConfig) -> Result<(), MamimiError> {
+        let pyenv_root = match &self.root {
+            Some(root) => root.parent().map(Path::to_path_buf),
+            None => std::env::var_os("PYENV_ROOT")
+                .map(PathBuf::from)
+                .or_else(|| home_dir().map(|home| home.join(".pyenv"))),
+        };
+        let global = pyenv_root
+            .and_then(|root| std::fs::read_to_string(root.join("version")).ok())
+            .and_then(|contents| contents.split_whitespace().next().map(str::to_string))
+            .and_then(|name| PythonVersion::parse(name).ok());
+        let version = match global {
+            Some(version) => version,
+            None => return Ok(()),
+        };
+        let installed = match &version {
+            PythonVersion::System => system_version::refresh(config)?.is_some(),
+            version => config.versions_dir().join(version.to_string()).exists(),
+        };
+        if !installed {
+            return Ok(());
+        }
+        let _lock = Lock::aliases(config)?;
+        create_alias(config, "default", &version)?;
+        outln!(
+            config,
+            Error,
+            "{} default now points to {}, pyenv's global version",
+            "==>".green(),
+            version
+        );
+        Ok(())
+    }
+}
+
+/// Checks that the installation at `prefix` runs and is the `expected` version.
+fn verify(prefix: &Path, expected: &PythonVersion) -> Result<(), MamimiError> {
+    let interpreter = find_in_prefix(prefix).ok_or_else(|| MamimiError::NoInterpreter {
+        prefix: prefix.to_path_buf(),
+    })?;
+    match probe_version(&interpreter) {
+        Some(version) if version == *expected => Ok(()),
+        Some(reported) => Err(MamimiError::UnexpectedVersion { reported }),
+        None => Err(MamimiError::InterpreterFailed { interpreter }),
+    }
+}
+
+/// Prints the prefix the interpreter runs from, then the libpython it loaded
+/// where the platform tells. Python 2 has no `base_prefix` and raises `IOError`.
+const PREFIX_SCRIPT: &str = "
+import sys
+print(getattr(sys, 'base_prefix', sys.prefix))
+try:
+    with open('/proc/self/maps') as maps:
+        for line in maps:
+            if 'libpython' in line:
+                print(line.split()[-1])
+except (IOError, OSError):
+    pass
+";
+
+/// Checks that the installation at `entry` runs on its own rather than from
+/// where it was copied from, which may be removed later on. A shared build
+/// whose RPATH names the original runs fine until then.
+fn check_independent(entry: &Path) -> Result<(), MamimiError> {
+    let interpreter = find_in_prefix(entry).ok_or_else(|| MamimiError::NoInterpreter {
+        prefix: entry.to_path_buf(),
+    })?;
+    let output = std::process::Command::new(&interpreter)
+        .arg("-c")
+        .arg(PREFIX_SCRIPT)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Err(MamimiError::InterpreterFailed { interpreter });
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines().map(PathBuf::from);
+    let base_prefix = lines.next().unwrap_or_default();
+    if !same_path(&base_prefix, entry) {
+        return Err(MamimiError::UsesOriginal { path: base_prefix });
+    }
+    let mut libraries = lines.collect::<Vec<_>>();
+    if cfg!(target_os = "macos") {
+        libraries.extend(linked_libraries(&interpreter));
+    }
+    let entry = entry.canonicalize()?;
+    match libraries
+        .into_iter()
+        // Names relative to the executable, as in `@rpath/libpython3.12.dylib`, move along
+        .find(|library| !library.starts_with(&entry) && !library.to_string_lossy().starts_with('@'))
+    {
+        Some(path) => Err(MamimiError::UsesOriginal { path }),
+        None => Ok(()),
+    }
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// The libpython an executable links against, as `otool -L` lists it.
+fn linked_libraries(executable: &Path) -> Vec<PathBuf> {
+    let output = match std::process::Command::new("otool")
+        .arg("-L")
+        .arg(executable)
+        .output()
+    {
+        Ok(output) => output,
+        Err(_) => return vec![],
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|library| library.contains("libpython") || library.contains("Python.framework"))
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Points the scripts in `bin`, such as `pip`, at the interpreter in `to`
+/// when their shebang names the one in `from`.
+fn rewrite_shebangs(bin: &Path, from: &Path, to: &Path) -> std::io::Result<()> {
+    let entries = match std::fs::read_dir(bin) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    let (from, to) = match (from.to_str(), to.to_str()) {
+        (Some(from), Some(to)) => (from, to),
+        _ => return Ok(()),
+    };
+    for entry in entries {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let mut first_line = vec![];
+        std::io::BufRead::read_until(
+            &mut std::io::BufReader::new(std::fs::File::open(&path)?),
+            b'\n',
+            &mut first_line,
+        )?;
+        let shebang = match std::str::from_utf8(&first_line) {
+            Ok(line) if line.starts_with("#!") && line.contains(from) => line.to_string(),
+            _ => continue,
+        };
+        let contents = std::fs::read(&path)?;
+        let mut rewritten = shebang.replace(from, to).into_bytes();
+        rewritten.extend_from_slice(&contents[shebang.len()..]);
+        std::fs::write(&path, rewritten)?;
+    }
+    Ok(())
+}
+
+/// Copies a directory tree, keeping symlinks as they are.
+fn copy_tree(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let destination = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            let target = std::fs::read_link(entry.path())?;
+            if entry.path().is_dir() {
+                crate::symlink::create_symlink_dir(target, &destination)?;
+            } else {
+                crate::symlink::create_symlink_file(target, &destination)?;
+            }
+        } else if file_type.is_dir() {
+            copy_tree(&entry.path(), &destination)?;
+        } else {
+            std::fs::copy(entry.path(), &destination)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An installation at `prefix` whose interpreter reports `version`, and
+    /// runs from `runs_from` instead of `prefix` when given.
+    #[cfg(unix)]
+    fn fake_python(prefix: &Path, version: &str, runs_from: Option<&Path>) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let bin = prefix.join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        let base_prefix = match runs_from {
+            Some(path) => path.display().to_string(),
+            None => "$(cd \"$(dirname \"$0\")/..\" && pwd)".to_string(),
+        };
+        let python = bin.join("python3");
+        std::fs::write(
+            &python,
+            format!(
+                "#!/bin/sh\ncase \"$2\" in\n  *version_info*) echo {} ;;\n  *) echo \"{}\" ;;\nesac\n",
+                version, base_prefix
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&python, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_version_of() {
+        let version = |source: Source, name| source.version_of(name).map(|v| v.to_string());
+        assert_eq!(version(Source::Pyenv, "3.12.1").unwrap(), "3.12.1");
+        assert_eq!(version(Source::Pyenv, "miniconda3-latest"), None);
+        assert_eq!(
+            version(Source::Uv, "cpython-3.12.4-linux-x86_64-gnu").unwrap(),
+            "3.12.4"
+        );
+        assert_eq!(version(Source::Uv, "cpython@3.11.9").unwrap(), "3.11.9");
+        assert_eq!(
+            version(Source::Uv, "cpython-3.13.0+freethreaded-linux-x86_64-gnu"),
+            None
+        );
+        assert_eq!(version(Source::Uv, "pypy-3.10.14-linux-x86_64-gnu"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_import_pyenv() {
+        use crate::alias::list_aliases;
+        use crate::commands::command::Command;
+
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let pyenv = tempfile::tempdir().unwrap();
+        for (name, reports) in [
+            ("3.11.6", "3.11.6"),
+            ("3.12.1", "3.12.1"),
+            ("3.10.2", "3.10.9"),
+        ] {
+            fake_python(&pyenv.path().join("versions").join(name), reports, None);
+        }
+        std::fs::write(pyenv.path().join("version"), "3.12.1\n").unwrap();
+
+        Import {
+            source: Source::Pyenv,
+            root: Some(pyenv.path().join("versions")),
+            move_: true,
+            link: false,
+            copy: false,
+        }
+        .apply(&config)
+        .unwrap();
+
+        let installed = crate::python_version::installed_versions(&config).unwrap();
+        let names = installed
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["3.11.6", "3.12.1"]);
+        assert!(!pyenv.path().join("versions/3.12.1").exists());
+        // The mislabeled installation stays where it was
+        assert!(pyenv.path().join("versions/3.10.2").exists());
+        let metadata = InstallMetadata::read(&config.versions_dir().join("3.11.6")).unwrap();
+        assert_eq!(metadata.origin, Origin::Imported);
+        let aliases = list_aliases(&config).unwrap();
+        assert_eq!(aliases[0].name(), "default");
+        assert_eq!(aliases[0].s_ver(), "3.12.1");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_copy_must_not_depend_on_the_original() {
+        use crate::commands::command::Command;
+
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let asdf = tempfile::tempdir().unwrap();
+        let relocatable = asdf.path().join("3.12.1");
+        fake_python(&relocatable, "3.12.1", None);
+        std::fs::write(
+            relocatable.join("bin/pip"),
+            format!("#!{}/bin/python3\nimport pip\n", relocatable.display()),
+        )
+        .unwrap();
+        let pinned = asdf.path().join("3.11.6");
+        fake_python(&pinned, "3.11.6", Some(&pinned));
+
+        Import {
+            source: Source::Asdf,
+            root: Some(asdf.path().to_path_buf()),
+            move_: false,
+            link: false,
+            copy: true,
+        }
+        .apply(&config)
+        .unwrap();
+
+        let installed = crate::python_version::installed_versions(&config).unwrap();
+        assert_eq!(installed, vec![PythonVersion::parse("3.12.1").unwrap()]);
+        let entry = config.versions_dir().join("3.12.1");
+        assert_eq!(
+            std::fs::read_to_string(entry.join("bin/pip")).unwrap(),
+            format!("#!{}/bin/python3\nimport pip\n", entry.display())
+        );
+        assert!(relocatable.exists());
+        assert!(!config.versions_dir().join("3.11.6").exists());
+        let candidate = Candidate {
+            version: PythonVersion::parse("3.11.6").unwrap(),
+            prefix: pinned.clone(),
+        };
+        let import = Import {
+            source: Source::Asdf,
+            root: None,
+            move_: false,
+            link: false,
+            copy: true,
+        };
+        assert!(matches!(
+            import.import(&config, &candidate),
+            Err(MamimiError::UsesOriginal { path }) if path == pinned
+        ));
+    }
+}
@@ -15,6 +15,8 @@ pub enum MamimiError {
     IoError(#[from] std::io::Error),
     #[error("Can't infer shell !")]
     CantInferShell,
+    #[error(transparent)]
+    ShellError(#[from] anyhow::Error),
 }
 
 #[derive(clap::Parser, Debug, Default)]
@@ -54,7 +56,7 @@ impl Command for Init {
         } else {
             mamimi_path.join("bin")
         };
-        println!("{:?}", shell.path(&binary_path));
+        println!("{}", shell.path(&binary_path)?);
         println!(
             "{}",
             shell.set_env_var(
@@ -1,10 +1,22 @@
 use crate::alias::create_alias;
 use crate::archive::{self, extract::Error as ExtractError, extract::Extract};
+use crate::build_config::{self, BuildConfig, BuildSettings};
+use crate::build_deps::{self, Preflight};
+use crate::build_log::{self, BuildLog, Progress};
+use crate::build_profile::BuildProfile;
 use crate::config::MamimiConfig;
 use crate::current_python_version::current_python_version;
+use crate::downloader::{self, Downloader};
+use crate::implementation::{AlternativeVersion, Implementation};
 use crate::input_version::InputVersion;
+use crate::install_metadata::{InstallMetadata, Origin};
+use crate::lock::Lock;
+use crate::openssl;
 use crate::outln;
 use crate::python_version::PythonVersion;
+use crate::release_status::warn_if_eol;
+use crate::stdlib_modules::{self, ModuleReport};
+use crate::symlink::create_symlink_file;
 use crate::version_files::get_user_version_for_directory;
 use anyhow::Result;
 use colored::Colorize;
@@ -26,6 +38,8 @@ pub enum MamimiError {
     HttpError(#[from] reqwest::Error),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    DownloadError(#[from] downloader::Error),
     #[error("Can't find the number of cores")]
     FromUtf8Error(#[from] std::string::FromUtf8Error),
     #[error("Can't extract the file: {source:?}")]
@@ -35,7 +49,7 @@ pub enum MamimiError {
     #[error("Can't find version")]
     VersionNotFound { version: InputVersion },
     #[error("Can't list the remote versions: {source:?}")]
-    CannotListRemoteVersions { source: reqwest::Error },
+    CannotListRemoteVersions { source: crate::http::Error },
     #[error("Version already installed at {path:?}")]
     VersionAlreadyInstalled { path: PathBuf },
     #[error(
@@ -44,142 +58,675 @@ pub enum MamimiError {
     CannotInferVersion,
     #[error("The requested version is not installable: {version}")]
     NotInstallableVerison { version: PythonVersion },
-    #[error("Cannot build Python: {stderr}")]
-    CannotBuildPython { stderr: String },
+    #[error("{step} failed:\n{tail}\n\nThe full build log is at {log:?}")]
+    BuildStepFailed {
+        step: &'static str,
+        tail: String,
+        log: PathBuf,
+    },
+    #[error("{preflight}\nPass --ignore-missing-deps to build anyway.")]
+    MissingBuildDependencies { preflight: Preflight },
+    #[error("Required modules failed to build: {}", modules.join(", "))]
+    MissingRequiredModules { modules: Vec<String> },
+    #[error(transparent)]
+    BuildConfigError(#[from] build_config::Error),
+    #[error("Invalid version {value:?} in {path:?}")]
+    InvalidVersionInFile { path: PathBuf, value: String },
+    #[error("{failed} of {total} installations failed")]
+    InstallationsFailed { failed: usize, total: usize },
+    #[error("{version} has no build for this platform")]
+    NoBuildForPlatform { version: PythonVersion },
+    #[error("{label} is not a build profile. Expected one of: optimized, debug, free-threaded")]
+    UnknownProfile { label: String },
+    #[error("The {profile} profile needs Python 3.13 or newer, not {version}")]
+    UnsupportedProfile {
+        profile: BuildProfile,
+        version: PythonVersion,
+    },
+    #[error("Can't find {executable} in {path:?}")]
+    ExecutableNotFound {
+        path: PathBuf,
+        executable: &'static str,
+    },
 }
 
 #[derive(clap::Parser, Debug, Default)]
 pub struct Install {
-    pub version: Option<InputVersion>,
+    /// The versions to install. Uses the version file of the current directory when omitted
+    pub versions: Vec<InputVersion>,
+    /// Also install the versions listed in this file, one per line
+    #[clap(long)]
+    pub from_file: Option<PathBuf>,
+    /// How many versions to build at the same time. The builds share the CPU cores
+    #[clap(long, short = 'j', default_value_t = 1)]
+    pub jobs: usize,
+    /// Extra options for `./configure`, after the ones from build.toml
+    #[clap(long = "configure-opt", allow_hyphen_values = true)]
     pub configure_opts: Vec<String>,
+    /// Extra options for `make`, after the ones from build.toml
+    #[clap(long = "make-opt", allow_hyphen_values = true)]
+    pub make_opts: Vec<String>,
+    /// Build even if required build dependencies seem to be missing
+    #[clap(long)]
+    pub ignore_missing_deps: bool,
+    /// Fail the installation when one of these stdlib modules does not build
+    #[clap(
+        long = "require-module",
+        env = "MAMIMI_REQUIRED_MODULES",
+        value_delimiter = ',',
+        default_value = "_ssl,_hashlib,zlib,_ctypes"
+    )]
+    pub required_modules: Vec<String>,
+    /// Build with a preset of options and install as e.g. `3.12.4-debug`:
+    /// optimized, debug or free-threaded
+    #[clap(long)]
+    pub profile: Option<BuildProfile>,
+}
+
+/// What happened to one of the requested versions.
+#[derive(Debug)]
+enum Outcome {
+    Installed(PythonVersion),
+    AlreadyInstalled(PythonVersion),
+    Failed(MamimiError),
 }
 
 impl crate::commands::command::Command for Install {
     type Error = MamimiError;
 
     fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
-        let current_version = self
-            .version
-            .clone()
-            .or_else(|| get_user_version_for_directory(std::env::current_dir().unwrap()))
-            .ok_or(MamimiError::CannotInferVersion)?;
-        let version = match current_version.clone() {
-            InputVersion::Full(PythonVersion::Semver(v)) => PythonVersion::Semver(v),
+        let requested = self.requested_versions()?;
+        let mut outcomes = self.install_all(config, &requested)?;
+
+        if !config.default_python_version_dir().exists() {
+            let first_installed = outcomes.iter().find_map(|outcome| match outcome {
+                Outcome::Installed(version) => Some(version),
+                _ => None,
+            });
+            if let Some(version) = first_installed {
+                let _lock = Lock::aliases(config)?;
+                if !config.default_python_version_dir().exists() {
+                    debug!("Use {} as the default Python version", version);
+                    create_alias(&config, "default", version).map_err(MamimiError::IoError)?;
+                }
+            }
+        }
+
+        if requested.len() == 1 {
+            return match outcomes.remove(0) {
+                Outcome::Installed(_) => Ok(()),
+                Outcome::AlreadyInstalled(version) => Err(MamimiError::VersionAlreadyInstalled {
+                    path: config.versions_dir().join(version.to_string()),
+                }),
+                Outcome::Failed(err) => Err(err),
+            };
+        }
+        print_summary(config, &requested, &outcomes);
+        let failed = outcomes
+            .iter()
+            .filter(|outcome| matches!(outcome, Outcome::Failed(_)))
+            .count();
+        if failed > 0 {
+            return Err(MamimiError::InstallationsFailed {
+                failed,
+                total: requested.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Install {
+    fn requested_versions(&self) -> Result<Vec<InputVersion>, MamimiError> {
+        let mut versions = self.versions.clone();
+        if let Some(path) = &self.from_file {
+            versions.extend(read_versions_file(path)?);
+        }
+        if versions.is_empty() {
+            versions.extend(get_user_version_for_directory(current_dir()?));
+        }
+        if versions.is_empty() {
+            return Err(MamimiError::CannotInferVersion);
+        }
+        Ok(versions)
+    }
+
+    /// Resolves, downloads and builds every requested version, returning an
+    /// outcome for each of them in the same order.
+    fn install_all(
+        &self,
+        config: &MamimiConfig,
+        requested: &[InputVersion],
+    ) -> Result<Vec<Outcome>, MamimiError> {
+        let mut remote_versions = None;
+        let mut outcomes = requested
+            .iter()
+            .map(
+                |input| match self.resolve(config, input, &mut remote_versions) {
+                    Ok(version) if config.versions_dir().join(version.to_string()).exists() => {
+                        Outcome::AlreadyInstalled(version)
+                    }
+                    Ok(version) => Outcome::Installed(version),
+                    Err(err) => Outcome::Failed(err),
+                },
+            )
+            .collect::<Vec<_>>();
+        let pending = outcomes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, outcome)| match outcome {
+                Outcome::Installed(version) => Some((index, version.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if pending.is_empty() {
+            return Ok(outcomes);
+        }
+
+        // Prebuilt implementations and MicroPython don't need CPython's libraries
+        #[cfg(unix)]
+        if pending
+            .iter()
+            .any(|(_, version)| matches!(version, PythonVersion::Semver(_)))
+        {
+            check_build_dependencies(config, self.ignore_missing_deps)?;
+        }
+        let build_config = BuildConfig::load(config)?;
+        let workers = self.jobs.clamp(1, pending.len());
+        // Progress lines of concurrent downloads and builds would overwrite each other
+        let show_progress = pending.len() == 1;
+
+        let downloads = std::thread::scope(|scope| {
+            let handles = pending
+                .iter()
+                .map(|(_, version)| scope.spawn(|| download(config, version, show_progress)))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("download thread panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        let queue = std::sync::Mutex::new(vec![]);
+        for ((index, version), download) in pending.into_iter().zip(downloads) {
+            match download {
+                Ok(archive) => queue.lock().unwrap().push((index, version, archive)),
+                Err(err) => outcomes[index] = Outcome::Failed(err),
+            }
+        }
+        queue.lock().unwrap().reverse();
+        let built = std::sync::Mutex::new(vec![]);
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let next = queue.lock().unwrap().pop();
+                    let (index, version, archive) = match next {
+                        Some(job) => job,
+                        None => break,
+                    };
+                    let mut settings = self.build_settings(&build_config, &version);
+                    settings
+                        .make_jobs
+                        .get_or_insert((num_cpus::get() / workers).max(1));
+                    let outcome = match self.build_locked(
+                        config,
+                        &version,
+                        &archive,
+                        settings,
+                        show_progress,
+                    ) {
+                        Ok(true) => Outcome::Installed(version),
+                        Ok(false) => Outcome::AlreadyInstalled(version),
+                        Err(err) => Outcome::Failed(err),
+                    };
+                    built.lock().unwrap().push((index, outcome));
+                });
+            }
+        });
+        for (index, outcome) in built.into_inner().unwrap() {
+            outcomes[index] = outcome;
+        }
+        Ok(outcomes)
+    }
+
+    fn resolve(
+        &self,
+        config: &MamimiConfig,
+        input: &InputVersion,
+        remote_versions: &mut Option<Vec<PythonVersion>>,
+    ) -> Result<PythonVersion, MamimiError> {
+        let version = match input {
+            InputVersion::Full(PythonVersion::Semver(v)) => PythonVersion::Semver(v.clone()),
             InputVersion::Full(PythonVersion::System) => {
                 return Err(MamimiError::NotInstallableVerison {
                     version: PythonVersion::System,
                 })
             }
-            current_version => {
-                let avalable_versions = crate::remote_python_index::list()
-                    .map_err(|source| MamimiError::CannotListRemoteVersions { source })?
-                    .drain(..)
-                    .map(|x| x.python_version)
-                    .collect::<Vec<_>>();
-                current_version
-                    .to_version(&avalable_versions)
-                    .ok_or(MamimiError::VersionNotFound {
-                        version: current_version,
+            InputVersion::Full(PythonVersion::Alternative(v)) => {
+                PythonVersion::Alternative(v.clone())
+            }
+            InputVersion::Alternative { implementation, .. } => {
+                let remote = crate::remote_python_index::list_alternative(config, *implementation)
+                    .map_err(|source| MamimiError::CannotListRemoteVersions { source })?;
+                input
+                    .to_version(remote.iter().map(|x| &x.python_version))
+                    .ok_or_else(|| MamimiError::VersionNotFound {
+                        version: input.clone(),
+                    })?
+                    .clone()
+            }
+            InputVersion::Labeled { label, .. } if input.profile().is_none() => {
+                return Err(MamimiError::UnknownProfile {
+                    label: label.clone(),
+                })
+            }
+            input => {
+                if remote_versions.is_none() {
+                    let remote = crate::remote_python_index::list(config)
+                        .map_err(|source| MamimiError::CannotListRemoteVersions { source })?;
+                    *remote_versions = Some(remote.into_iter().map(|x| x.python_version).collect());
+                }
+                input
+                    .without_profile()
+                    .to_version(remote_versions.iter().flatten())
+                    .ok_or_else(|| MamimiError::VersionNotFound {
+                        version: input.clone(),
                     })?
                     .clone()
             }
         };
-        let installations_dir = config.versions_dir();
-        let installation_dir = PathBuf::from(&installations_dir).join(version.to_string());
-
-        if installation_dir.exists() {
-            return Err(MamimiError::VersionAlreadyInstalled {
-                path: installation_dir,
-            });
+        let profile = self
+            .profile
+            .or_else(|| input.profile())
+            .or_else(|| version.profile());
+        // Older releases ignore `--disable-gil` and would build with the GIL
+        if let (Some(BuildProfile::FreeThreaded), PythonVersion::Semver(v)) = (profile, &version) {
+            if (v.major, v.minor) < (3, 13) {
+                return Err(MamimiError::UnsupportedProfile {
+                    profile: BuildProfile::FreeThreaded,
+                    version,
+                });
+            }
         }
+        let version = version.with_profile(profile);
+        warn_if_eol(config, &version);
+        Ok(version)
+    }
 
-        let url = package_url(&version);
-        outln!(
-            config,
-            Error,
-            "{} Downloading {}",
-            "==>".green(),
-            format!("{}", url).green()
-        );
-        let response = reqwest::blocking::get(url.clone())?;
-        if response.status() == 404 {
-            return Err(MamimiError::VersionNotFound {
-                version: current_version,
-            });
+    /// The profile's options, then build.toml, then the command line.
+    fn build_settings(&self, build_config: &BuildConfig, version: &PythonVersion) -> BuildSettings {
+        let profile_settings = BuildSettings {
+            configure_opts: version
+                .profile()
+                .map(|profile| profile.configure_opts())
+                .unwrap_or_default()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            ..BuildSettings::default()
+        };
+        profile_settings
+            .merge(&build_config.settings_for(version))
+            .merge(&BuildSettings {
+                configure_opts: self.configure_opts.clone(),
+                make_opts: self.make_opts.clone(),
+                ..BuildSettings::default()
+            })
+    }
+
+    /// Builds `version` while holding its lock, unless another process
+    /// installed it in the meantime. Returns whether it was built.
+    fn build_locked(
+        &self,
+        config: &MamimiConfig,
+        version: &PythonVersion,
+        archive_path: &Path,
+        build_settings: BuildSettings,
+        show_progress: bool,
+    ) -> Result<bool, MamimiError> {
+        let _lock = Lock::version(config, version)?;
+        if config.versions_dir().join(version.to_string()).exists() {
+            return Ok(false);
         }
+        self.build(config, version, archive_path, build_settings, show_progress)?;
+        Ok(true)
+    }
 
+    fn build(
+        &self,
+        config: &MamimiConfig,
+        version: &PythonVersion,
+        archive_path: &Path,
+        build_settings: BuildSettings,
+        show_progress: bool,
+    ) -> Result<(), MamimiError> {
+        let installations_dir = config.versions_dir();
+        let installation_dir = installations_dir.join(version.to_string());
         outln!(
             config,
             Error,
             "{} Extracting {}",
             "==>".green(),
-            format!("{}", url).green()
+            archive_path.display().to_string().green()
         );
         let tmp_installations_dir = installations_dir.join(".downloads");
         std::fs::create_dir_all(&tmp_installations_dir).map_err(MamimiError::IoError)?;
         let tmp_dir = tempfile::TempDir::new_in(&tmp_installations_dir)
             .expect("Cannot generate a temp directory");
-        extract_archive_into(&tmp_dir, response)?;
+        if let Err(err) = extract_archive_into(&tmp_dir, archive_path) {
+            // Don't keep a corrupted archive around for the next attempt
+            std::fs::remove_file(archive_path)?;
+            return Err(err);
+        }
 
-        outln!(
-            config,
-            Error,
-            "{} Building {}",
-            "==>".green(),
-            format!("Python {}", current_version).green()
-        );
         let installed_directory = std::fs::read_dir(&tmp_dir)
             .map_err(MamimiError::IoError)?
             .next()
             .ok_or(MamimiError::TarIsEmpty)?
             .map_err(MamimiError::IoError)?;
         let installed_directory = installed_directory.path();
-        build_package(
+        if let Err(err) = self.install_extracted(
+            config,
+            version,
             &installed_directory,
             &installation_dir,
-            &self.configure_opts,
-        )?;
+            build_settings,
+            show_progress,
+        ) {
+            // A partial installation would pass for an installed version later on
+            if installation_dir.exists() {
+                if let Err(cleanup) = std::fs::remove_dir_all(&installation_dir) {
+                    debug!("Can't remove {:?}: {}", installation_dir, cleanup);
+                }
+            }
+            return Err(err);
+        }
+        outln!(
+            config,
+            Error,
+            "{} Installed {}",
+            "==>".green(),
+            format!("Python {}", version).green()
+        );
+        Ok(())
+    }
 
-        if !config.default_python_version_dir().exists() {
-            debug!("Use {} as the default Python version", current_version);
-            create_alias(&config, "default", &version).map_err(MamimiError::IoError)?;
+    /// Builds or moves the extracted `source_dir` into `installation_dir` and
+    /// records how it got there.
+    fn install_extracted(
+        &self,
+        config: &MamimiConfig,
+        version: &PythonVersion,
+        source_dir: &Path,
+        installation_dir: &Path,
+        build_settings: BuildSettings,
+        show_progress: bool,
+    ) -> Result<(), MamimiError> {
+        let origin = match version {
+            PythonVersion::Alternative(alternative) => install_alternative(
+                config,
+                alternative,
+                source_dir,
+                installation_dir,
+                &build_settings,
+                show_progress,
+            )?,
+            _ => {
+                outln!(
+                    config,
+                    Error,
+                    "{} Building {}",
+                    "==>".green(),
+                    format!("Python {}", version).green()
+                );
+                let log = BuildLog::create(config, version)?;
+                debug!("Writing the build log to {:?}", log.path());
+                build_package(
+                    config,
+                    &log,
+                    source_dir,
+                    installation_dir,
+                    &build_settings,
+                    &self.required_modules,
+                    show_progress,
+                )?;
+                Origin::Source
+            }
+        };
+        InstallMetadata {
+            version: version.to_string(),
+            installed_at: chrono::Local::now().to_rfc3339(),
+            origin,
+            linked_to: None,
+            profile: version.profile(),
+            build: match origin {
+                Origin::Source => build_settings,
+                _ => BuildSettings::default(),
+            },
         }
+        .write(installation_dir)?;
         Ok(())
     }
 }
 
-fn extract_archive_into<P: AsRef<Path>>(
-    path: P,
-    response: reqwest::blocking::Response,
+/// Reads versions separated by whitespace or new lines, ignoring `#` comments.
+fn read_versions_file(path: &Path) -> Result<Vec<InputVersion>, MamimiError> {
+    let contents = std::fs::read_to_string(path)?;
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(str::split_whitespace)
+        .map(|word| {
+            word.parse().map_err(|_| MamimiError::InvalidVersionInFile {
+                path: path.to_path_buf(),
+                value: word.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn download(
+    config: &MamimiConfig,
+    version: &PythonVersion,
+    show_progress: bool,
+) -> Result<PathBuf, MamimiError> {
+    let url = match version {
+        PythonVersion::Alternative(alternative) => {
+            alternative
+                .package_url()
+                .ok_or_else(|| MamimiError::NoBuildForPlatform {
+                    version: version.clone(),
+                })?
+        }
+        version => package_url(config, &version.without_profile()),
+    };
+    outln!(
+        config,
+        Error,
+        "{} Downloading {}",
+        "==>".green(),
+        crate::http::redact(&url).to_string().green()
+    );
+    let archive_path = downloader::cache_path(config, &url);
+    let file_name = archive_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let _lock = Lock::download(config, &file_name)?;
+    let downloader = Downloader::new(config)?.show_progress(show_progress);
+    match downloader.download(&url, &archive_path) {
+        Err(downloader::Error::NotFound { .. }) => Err(MamimiError::VersionNotFound {
+            version: InputVersion::Full(version.clone()),
+        }),
+        result => Ok(result.map(|_| archive_path)?),
+    }
+}
+
+fn print_summary(config: &MamimiConfig, requested: &[InputVersion], outcomes: &[Outcome]) {
+    outln!(config, Error, "{} Summary", "==>".green());
+    let width = requested
+        .iter()
+        .map(|input| input.to_string().len())
+        .max()
+        .unwrap_or(0);
+    for (input, outcome) in requested.iter().zip(outcomes) {
+        let status = match outcome {
+            Outcome::Installed(version) => format!("installed {}", version).green(),
+            Outcome::AlreadyInstalled(version) => {
+                format!("{} is already installed", version).normal()
+            }
+            Outcome::Failed(err) => format!(
+                "failed: {}",
+                err.to_string().lines().next().unwrap_or_default()
+            )
+            .red(),
+        };
+        let input = format!("{:width$}", input.to_string(), width = width);
+        outln!(config, Error, "    {}  {}", input, status);
+    }
+}
+
+#[cfg(unix)]
+fn check_build_dependencies(
+    config: &MamimiConfig,
+    ignore_missing_deps: bool,
 ) -> Result<(), MamimiError> {
-    #[cfg(unix)]
-    let extractor = archive::tar_xz::TarXz::new(response);
-    #[cfg(windows)]
-    let extractor = archive::tar_xz::TarXz::new(response);
-    extractor
-        .extract_into(path)
-        .map_err(|source| MamimiError::ExtractError { source })?;
+    outln!(
+        config,
+        Error,
+        "{} Checking build dependencies",
+        "==>".green()
+    );
+    let preflight = build_deps::preflight();
+    if preflight.missing.is_empty() {
+        return Ok(());
+    }
+    if preflight.missing_required().next().is_none() || ignore_missing_deps {
+        outln!(
+            config,
+            Error,
+            "{} {}",
+            "warning:".yellow().bold(),
+            preflight.to_string().yellow()
+        );
+        return Ok(());
+    }
+    Err(MamimiError::MissingBuildDependencies { preflight })
+}
+
+fn extract_archive_into<P: AsRef<Path>>(path: P, archive_path: &Path) -> Result<(), MamimiError> {
+    let response = std::fs::File::open(archive_path)?;
+    // PyPy ships bzip2 and GraalPy gzip archives, CPython and MicroPython xz ones
+    let file_name = archive_path.to_string_lossy();
+    let result = if file_name.ends_with(".tar.bz2") {
+        archive::tar_bz2::TarBz2::new(response).extract_into(path)
+    } else if file_name.ends_with(".tar.gz") {
+        archive::tar_gz::TarGz::new(response).extract_into(path)
+    } else {
+        archive::tar_xz::TarXz::new(response).extract_into(path)
+    };
+    result.map_err(|source| MamimiError::ExtractError { source })?;
     Ok(())
 }
 
+/// Puts a PyPy or GraalPy installation in place, or builds MicroPython's unix
+/// port from `source_dir`.
+fn install_alternative(
+    config: &MamimiConfig,
+    version: &AlternativeVersion,
+    source_dir: &Path,
+    installation_dir: &Path,
+    settings: &BuildSettings,
+    show_progress: bool,
+) -> Result<Origin, MamimiError> {
+    let implementation = version.implementation;
+    let executable = implementation.executable();
+    let bin = installation_dir.join("bin");
+    if implementation == Implementation::MicroPython {
+        outln!(
+            config,
+            Error,
+            "{} Building {}",
+            "==>".green(),
+            version.to_string().green()
+        );
+        let log = BuildLog::create(config, &PythonVersion::Alternative(version.clone()))?;
+        debug!("Writing the build log to {:?}", log.path());
+        for (step, dir) in [("make mpy-cross", "mpy-cross"), ("make", "ports/unix")] {
+            run_step(
+                config,
+                &log,
+                settings,
+                step,
+                Command::new("make")
+                    .arg("-j")
+                    .arg(settings.make_jobs().to_string())
+                    .args(&settings.make_opts)
+                    .arg("-C")
+                    .arg(dir)
+                    .current_dir(source_dir),
+                show_progress,
+            )?;
+        }
+        // Releases before 1.20 build into the port's directory itself
+        let built = ["ports/unix/build-standard", "ports/unix"]
+            .iter()
+            .map(|dir| source_dir.join(dir).join(executable))
+            .find(|path| path.is_file())
+            .ok_or_else(|| MamimiError::ExecutableNotFound {
+                path: source_dir.join("ports/unix"),
+                executable,
+            })?;
+        std::fs::create_dir_all(&bin)?;
+        std::fs::copy(built, bin.join(executable))?;
+        return Ok(Origin::Source);
+    }
+
+    outln!(
+        config,
+        Error,
+        "{} Installing {}",
+        "==>".green(),
+        version.to_string().green()
+    );
+    // The prebuilt archives hold a relocatable installation
+    std::fs::rename(source_dir, installation_dir)?;
+    if !bin.join(executable).exists() {
+        std::fs::remove_dir_all(installation_dir)?;
+        return Err(MamimiError::ExecutableNotFound {
+            path: bin,
+            executable,
+        });
+    }
+    for name in ["python", "python3"] {
+        if std::fs::symlink_metadata(bin.join(name)).is_err() {
+            create_symlink_file(executable, bin.join(name))?;
+        }
+    }
+    Ok(Origin::Prebuilt)
+}
+
+fn mirror_url(config: &MamimiConfig, path: &str) -> Url {
+    let mut mirror = config.python_ftp_mirror.clone();
+    if !mirror.path().ends_with('/') {
+        mirror.set_path(&format!("{}/", mirror.path()));
+    }
+    mirror.join(path).expect("Can't build the package url")
+}
+
 #[cfg(unix)]
-fn package_url(version: &PythonVersion) -> Url {
+fn package_url(config: &MamimiConfig, version: &PythonVersion) -> Url {
     debug!("package url");
-    Url::parse(&format!(
-        "https://www.python.org/ftp/python/{}/Python-{}.tar.xz",
-        version, version
-    ))
-    .unwrap()
+    mirror_url(config, &format!("{}/Python-{}.tar.xz", version, version))
 }
 
 #[cfg(windows)]
-fn package_url(version: &PythonVersion) -> Url {
+fn package_url(config: &MamimiConfig, version: &PythonVersion) -> Url {
     debug!("package url");
-    Url::parse(&format!(
-        "https://www.python.org/ftp/python/{}/Python-{}-embed-amd64.zip",
-        version, version
-    ))
-    .unwrap()
+    mirror_url(
+        config,
+        &format!("{}/Python-{}-embed-amd64.zip", version, version),
+    )
 }
 
 #[cfg(unix)]
@@ -192,28 +739,16 @@ fn archive(version: &PythonVersion) -> String {
     format!("python-{}.zip", version)
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn openssl_dir() -> Result<String, MamimiError> {
-    #[cfg(target_os = "macos")]
-    return Ok(String::from_utf8_lossy(
-        &Command::new("brew")
-            .arg("--prefix")
-            .arg("openssl@1.1")
-            .output()
-            .map_err(MamimiError::IoError)?
-            .stdout,
-    )
-    .trim()
-    .to_string());
-    #[cfg(not(target_os = "macos"))]
-    return Ok("/url/local".to_string());
-}
-
 fn build_package(
+    config: &MamimiConfig,
+    log: &BuildLog,
     current_dir: &Path,
     installed_dir: &Path,
-    configure_opts: &[String],
+    settings: &BuildSettings,
+    required_modules: &[String],
+    show_progress: bool,
 ) -> Result<(), MamimiError> {
+    let configure_opts = &settings.configure_opts;
     debug!("./configure {}", configure_opts.join(" "));
     let mut command = Command::new("sh");
     command
@@ -221,44 +756,112 @@ fn build_package(
         .arg(format!("--prefix={}", installed_dir.to_str().unwrap()))
         .args(configure_opts);
 
-    // Provide a default value for --with-openssl-dir
+    // Point configure to OpenSSL when it lives outside of the compiler's search path
     if !configure_opts
         .iter()
         .any(|opt| opt.starts_with("--with-openssl-dir"))
     {
-        command.arg(format!("--with-openssl-dir={}", openssl_dir()?));
+        if let Some(openssl_dir) = openssl::configure_dir() {
+            debug!("Use OpenSSL at {:?}", openssl_dir);
+            command.arg(format!("--with-openssl-dir={}", openssl_dir.display()));
+        }
     }
+    run_step(
+        config,
+        log,
+        settings,
+        "configure",
+        command.current_dir(current_dir),
+        show_progress,
+    )?;
 
-    let configure = command
-        .current_dir(&current_dir)
-        .output()
-        .map_err(MamimiError::IoError)?;
-    if !configure.status.success() {
-        return Err(MamimiError::CannotBuildPython {
-            stderr: format!(
-                "configure failed: {}",
-                String::from_utf8_lossy(&configure.stderr).to_string()
-            ),
-        });
-    };
-    debug!("make -j {}", num_cpus::get().to_string());
-    let make = Command::new("make")
-        .arg("-j")
-        .arg(num_cpus::get().to_string())
-        .current_dir(&current_dir)
-        .output()
-        .map_err(MamimiError::IoError)?;
-    if !make.status.success() {
-        return Err(MamimiError::CannotBuildPython {
-            stderr: format!(
-                "make failed: {}",
-                String::from_utf8_lossy(&make.stderr).to_string()
-            ),
+    debug!(
+        "make -j {} {}",
+        settings.make_jobs(),
+        settings.make_opts.join(" ")
+    );
+    let build_output = run_step(
+        config,
+        log,
+        settings,
+        "make",
+        Command::new("make")
+            .arg("-j")
+            .arg(settings.make_jobs().to_string())
+            .args(&settings.make_opts)
+            .current_dir(current_dir),
+        show_progress,
+    )?;
+
+    let report = ModuleReport::new(
+        &build_output,
+        stdlib_modules::built_interpreter(current_dir).as_deref(),
+    );
+    outln!(
+        config,
+        Error,
+        "{} Optional modules\n{}",
+        "==>".green(),
+        report
+    );
+    let missing = report.missing_required(required_modules);
+    if !missing.is_empty() {
+        return Err(MamimiError::MissingRequiredModules {
+            modules: missing.into_iter().cloned().collect(),
         });
-    };
+    }
+    if !report.missing.is_empty() {
+        outln!(
+            config,
+            Error,
+            "{} some optional modules failed to build: {}",
+            "warning:".yellow().bold(),
+            report
+                .missing
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    debug!("make install");
+    run_step(
+        config,
+        log,
+        settings,
+        "make install",
+        Command::new("make").arg("install").current_dir(current_dir),
+        show_progress,
+    )?;
     Ok(())
 }
 
+/// Runs one build step with its output going to the build log and a progress line.
+fn run_step(
+    config: &MamimiConfig,
+    log: &BuildLog,
+    settings: &BuildSettings,
+    step: &'static str,
+    command: &mut Command,
+    show_progress: bool,
+) -> Result<String, MamimiError> {
+    settings.apply_env(command);
+    let progress =
+        Progress::new(config, format!("{} Running {}", "==>".green(), step)).enabled(show_progress);
+    let result = log.run(step, command, &|line| progress.update(line));
+    progress.finish();
+    let (status, output) = result?;
+    if !status.success() {
+        return Err(MamimiError::BuildStepFailed {
+            step,
+            tail: log.tail(build_log::TAIL_LINES),
+            log: log.path().to_path_buf(),
+        });
+    }
+    Ok(output)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -274,10 +877,16 @@ mod tests {
         let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
 
         Install {
-            version: Some(InputVersion::Full(PythonVersion::Semver(
+            versions: vec![InputVersion::Full(PythonVersion::Semver(
                 semver::Version::parse("3.9.6").unwrap(),
-            ))),
+            ))],
+            from_file: None,
+            jobs: 1,
             configure_opts: vec![],
+            make_opts: vec![],
+            ignore_missing_deps: false,
+            required_modules: vec![],
+            profile: None,
         }
         .apply(&config)
         .expect("Can't install Python3.9.6");
@@ -297,10 +906,16 @@ mod tests {
         let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
 
         Install {
-            version: Some(InputVersion::Full(PythonVersion::Semver(
+            versions: vec![InputVersion::Full(PythonVersion::Semver(
                 semver::Version::parse("3.9.6").unwrap(),
-            ))),
+            ))],
+            from_file: None,
+            jobs: 1,
             configure_opts: vec![],
+            make_opts: vec![],
+            ignore_missing_deps: false,
+            required_modules: vec![],
+            profile: None,
         }
         .apply(&config)
         .expect("Can't insatll");
@@ -314,4 +929,121 @@ mod tests {
             .exists());
         assert!(config.default_python_version_dir().exists());
     }
+
+    #[test]
+    fn test_read_versions_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("versions.txt");
+        std::fs::write(&path, "# CI matrix\n3.9 3.10\n\n3.11.4  # pinned\n").unwrap();
+        assert_eq!(
+            read_versions_file(&path)
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect_vec(),
+            vec!["3.9.x", "3.10.x", "3.11.4"]
+        );
+
+        std::fs::write(&path, "3.9 not-a-version\n").unwrap();
+        assert!(matches!(
+            read_versions_file(&path),
+            Err(MamimiError::InvalidVersionInFile { value, .. }) if value == "not-a-version"
+        ));
+    }
+
+    #[test]
+    fn test_free_threaded_needs_3_13() {
+        let config = MamimiConfig::default();
+        let install = Install {
+            profile: Some(BuildProfile::FreeThreaded),
+            ..Install::default()
+        };
+        let resolve =
+            |version: &str| install.resolve(&config, &version.parse().unwrap(), &mut None);
+        assert!(matches!(
+            resolve("3.12.4"),
+            Err(MamimiError::UnsupportedProfile { .. })
+        ));
+        assert_eq!(
+            resolve("3.13.0").unwrap().to_string(),
+            "3.13.0-free-threaded"
+        );
+        assert!(matches!(
+            Install::default().resolve(&config, &"3.12-opt".parse().unwrap(), &mut None),
+            Err(MamimiError::UnknownProfile { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_install_prebuilt_alternative() {
+        let base_dir = tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let extracted = base_dir.path().join("pypy3.10-v7.3.16-linux64");
+        std::fs::create_dir_all(extracted.join("bin")).unwrap();
+        std::fs::write(extracted.join("bin/pypy3"), "").unwrap();
+        let version = match PythonVersion::parse("pypy3.10-7.3.16").unwrap() {
+            PythonVersion::Alternative(version) => version,
+            version => panic!("{} isn't an alternative implementation", version),
+        };
+        let installation_dir = config.versions_dir().join(version.to_string());
+
+        let origin = install_alternative(
+            &config,
+            &version,
+            &extracted,
+            &installation_dir,
+            &BuildSettings::default(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(origin, Origin::Prebuilt);
+        assert!(!extracted.exists());
+        assert_eq!(
+            std::fs::read_link(installation_dir.join("bin/python3")).unwrap(),
+            PathBuf::from("pypy3")
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_failed_build_leaves_no_installation() {
+        let base_dir = tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let source = base_dir.path().join("Python-3.12.4");
+        std::fs::create_dir_all(&source).unwrap();
+        // `make install` fails after it has created the prefix
+        std::fs::write(
+            source.join("configure"),
+            "prefix=${1#--prefix=}\nprintf 'all:\\n\\ttrue\\ninstall:\\n\\tmkdir -p %s/bin\\n\\tfalse\\n' \"$prefix\" > Makefile\n",
+        )
+        .unwrap();
+        let archive_path = base_dir.path().join("Python-3.12.4.tar.gz");
+        let status = std::process::Command::new("tar")
+            .arg("czf")
+            .arg(&archive_path)
+            .arg("-C")
+            .arg(base_dir.path())
+            .arg("Python-3.12.4")
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let version = PythonVersion::parse("3.12.4").unwrap();
+
+        let result = Install::default().build(
+            &config,
+            &version,
+            &archive_path,
+            BuildSettings::default(),
+            false,
+        );
+        assert!(matches!(
+            result,
+            Err(MamimiError::BuildStepFailed {
+                step: "make install",
+                ..
+            })
+        ));
+        assert!(!config.versions_dir().join("3.12.4").exists());
+    }
 }
@@ -6,7 +6,7 @@ use crate::config;
 #[derive(Error, Debug)]
 pub enum MamimiError {
     #[error(transparent)]
-    HttpError(#[from] reqwest::Error),
+    HttpError(#[from] crate::http::Error),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
 }
@@ -17,7 +17,7 @@ impl crate::commands::command::Command for InstallList {
     type Error = MamimiError;
 
     fn apply(self, config: &crate::config::MamimiConfig) -> Result<(), MamimiError> {
-        let versions = crate::remote_python_index::list()?;
+        let versions = crate::remote_python_index::list(config)?;
         let versions = versions
             .into_iter()
             .map(|v| v.python_version)
@@ -0,0 +1,244 @@
+use crate::build_profile::BuildProfile;
+use crate::config::MamimiConfig;
+use crate::executable::is_executable;
+use crate::input_version::InputVersion;
+use crate::install_metadata::{InstallMetadata, Origin};
+use crate::interpreter::{find_in_prefix, probe_version};
+use crate::lock::Lock;
+use crate::outln;
+use crate::python_version::PythonVersion;
+use crate::symlink::{create_symlink_dir, create_symlink_file};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MamimiError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Can't find a Python interpreter at {path:?}")]
+    InterpreterNotFound { path: PathBuf },
+    #[error("Can't get the version of {path:?}")]
+    CannotProbe { path: PathBuf },
+    #[error("{name} is not a valid name. Use a version such as 3.12.4 or 3.12-opt")]
+    InvalidName { name: String },
+    #[error("{path:?} is Python {version}, which doesn't match {name}")]
+    NameMismatch {
+        path: PathBuf,
+        version: PythonVersion,
+        name: String,
+    },
+    #[error("{label} names a build profile. Pick another label")]
+    ProfileLabel { label: String },
+    #[error("Version already installed at {path:?}")]
+    VersionAlreadyInstalled { path: PathBuf },
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct Link {
+    /// An interpreter, or an installation prefix such as `/opt/python3.12`
+    pub path: PathBuf,
+    /// The name to use instead of the interpreter's version. A label such as
+    /// `3.12-opt` is completed to e.g. `3.12.4-opt`, and `3.12-opt` resolves to it
+    #[clap(long = "as", value_name = "NAME")]
+    pub name: Option<String>,
+}
+
+impl crate::commands::command::Command for Link {
+    type Error = MamimiError;
+
+    fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
+        let not_found = || MamimiError::InterpreterNotFound {
+            path: self.path.clone(),
+        };
+        let path = std::fs::canonicalize(&self.path).map_err(|_| not_found())?;
+        // A prefix brings its whole `bin` directory, a lone interpreter gets one of its own
+        let (interpreter, bin_dir) = if path.is_dir() {
+            let interpreter = find_in_prefix(&path).ok_or_else(not_found)?;
+            let bin_dir = interpreter.parent().map(Path::to_path_buf);
+            (interpreter, bin_dir)
+        } else if is_executable(&path) {
+            (path.clone(), None)
+        } else {
+            return Err(not_found());
+        };
+        let version = probe_version(&interpreter).ok_or_else(|| MamimiError::CannotProbe {
+            path: interpreter.clone(),
+        })?;
+        let name = entry_name(&version, self.name.as_deref()).map_err(|err| match err {
+            MamimiError::NameMismatch { version, name, .. } => MamimiError::NameMismatch {
+                path: interpreter.clone(),
+                version,
+                name,
+            },
+            err => err,
+        })?;
+
+        let metadata = InstallMetadata {
+            version: version.to_string(),
+            installed_at: chrono::Local::now().to_rfc3339(),
+            origin: Origin::Linked,
+            linked_to: Some(path.clone()),
+            ..InstallMetadata::default()
+        };
+        link_entry(config, &name, &interpreter, bin_dir.as_deref(), &metadata)?;
+        outln!(
+            config,
+            Error,
+            "{} Linked {} as {}",
+            "==>".green(),
+            path.display(),
+            name.to_string().green()
+        );
+        Ok(())
+    }
+}
+
+/// Creates the entry `name` in the versions directory from links to
+/// `bin_dir`, or to `interpreter` alone when there's no `bin_dir` to share.
+pub fn link_entry(
+    config: &MamimiConfig,
+    name: &PythonVersion,
+    interpreter: &Path,
+    bin_dir: Option<&Path>,
+    metadata: &InstallMetadata,
+) -> Result<PathBuf, MamimiError> {
+    let _lock = Lock::version(config, name)?;
+    let entry = config.versions_dir().join(name.to_string());
+    if std::fs::symlink_metadata(&entry).is_ok() {
+        return Err(MamimiError::VersionAlreadyInstalled { path: entry });
+    }
+    std::fs::create_dir(&entry)?;
+    let linked =
+        create_entry(&entry, interpreter, name, bin_dir).and_then(|()| metadata.write(&entry));
+    if let Err(err) = linked {
+        std::fs::remove_dir_all(&entry)?;
+        return Err(err.into());
+    }
+    Ok(entry)
+}
+
+fn create_entry(
+    entry: &Path,
+    interpreter: &Path,
+    name: &PythonVersion,
+    bin_dir: Option<&Path>,
+) -> std::io::Result<()> {
+    if let Some(bin_dir) = bin_dir {
+        return create_symlink_dir(bin_dir, entry.join("bin"));
+    }
+    let bin = entry.join("bin");
+    std::fs::create_dir(&bin)?;
+    let mut names = vec!["python".to_string(), "python3".to_string()];
+    if let PythonVersion::Semver(v) = name {
+        names.push(format!("python{}.{}", v.major, v.minor));
+    }
+    for name in names {
+        create_symlink_file(interpreter, bin.join(name))?;
+    }
+    Ok(())
+}
+
+/// The probed `version`, or the requested `name` completed with it.
+fn entry_name(version: &PythonVersion, name: Option<&str>) -> Result<PythonVersion, MamimiError> {
+    let name = match name {
+        Some(name) => name,
+        None => return Ok(version.clone()),
+    };
+    let invalid = || MamimiError::InvalidName {
+        name: name.to_string(),
+    };
+    let (series, label) = match name.split_once('-') {
+        Some((series, label)) => (series, Some(label)),
+        None => (name, None),
+    };
+    let series: InputVersion = series.parse().map_err(|_| invalid())?;
+    let mut semver = match version {
+        PythonVersion::Semver(v) if series.matches(version) => v.clone(),
+        _ => {
+            return Err(MamimiError::NameMismatch {
+                path: PathBuf::new(),
+                version: version.clone(),
+                name: name.to_string(),
+            })
+        }
+    };
+    if let Some(label) = label {
+        // The entry would pass for a build of that profile
+        if label.parse::<BuildProfile>().is_ok() {
+            return Err(MamimiError::ProfileLabel {
+                label: label.to_string(),
+            });
+        }
+        semver.pre = semver::Prerelease::new(label).map_err(|_| invalid())?;
+    }
+    Ok(PythonVersion::Semver(semver))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_name() {
+        let version = PythonVersion::parse("3.12.4").unwrap();
+        let name = |name| entry_name(&version, name).map(|v| v.to_string());
+        assert_eq!(name(None).unwrap(), "3.12.4");
+        assert_eq!(name(Some("3.12-opt")).unwrap(), "3.12.4-opt");
+        assert_eq!(name(Some("3.12.4-opt")).unwrap(), "3.12.4-opt");
+        assert!(matches!(
+            name(Some("3.11-opt")),
+            Err(MamimiError::NameMismatch { .. })
+        ));
+        assert!(matches!(
+            name(Some("opt")),
+            Err(MamimiError::InvalidName { .. })
+        ));
+        assert!(matches!(
+            name(Some("3.12-debug")),
+            Err(MamimiError::ProfileLabel { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_link_a_prefix() {
+        use crate::commands::command::Command;
+        use std::os::unix::fs::PermissionsExt;
+
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let prefix = tempfile::tempdir().unwrap();
+        std::fs::create_dir(prefix.path().join("bin")).unwrap();
+        let python = prefix.path().join("bin/python3");
+        std::fs::write(&python, "#!/bin/sh\necho 3.12.4\n").unwrap();
+        std::fs::set_permissions(&python, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        Link {
+            path: prefix.path().to_path_buf(),
+            name: Some("3.12-opt".to_string()),
+        }
+        .apply(&config)
+        .unwrap();
+
+        let entry = config.versions_dir().join("3.12.4-opt");
+        assert!(entry.join("bin/python3").exists());
+        let metadata = InstallMetadata::read(&entry).unwrap();
+        assert_eq!(metadata.origin, Origin::Linked);
+        assert_eq!(metadata.version, "3.12.4");
+
+        // The name given to --as finds the entry again
+        let installed = crate::python_version::installed_versions(&config).unwrap();
+        let input: InputVersion = "3.12-opt".parse().unwrap();
+        assert_eq!(
+            input.to_version(&installed).unwrap().to_string(),
+            "3.12.4-opt"
+        );
+        assert_eq!(input.profile(), None);
+        crate::commands::global::Global { version: input }
+            .apply(&config)
+            .unwrap();
+        let aliases = crate::alias::list_aliases(&config).unwrap();
+        assert_eq!(aliases[0].s_ver(), "3.12.4-opt");
+    }
+}
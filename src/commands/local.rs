@@ -1,5 +1,8 @@
 use crate::input_version::InputVersion;
+use crate::python_version::{installed_versions, PythonVersion};
+use crate::release_status::warn_if_eol;
 use crate::symlink::{create_symlink_dir, remove_symlink_dir};
+use crate::system_version;
 use crate::version_files::get_user_version_for_directory;
 use log::debug;
 use thiserror::Error;
@@ -16,6 +19,8 @@ pub enum MamimiError {
     VersionNotFound { version: InputVersion },
     #[error("Can't find version in dotfiles. Please provide a version manually to the command.")]
     CannotInferVersion,
+    #[error("Can't find a Python interpreter on PATH outside of mamimi's directories")]
+    NoSystemInterpreter,
 }
 
 #[derive(clap::Parser, Debug)]
@@ -47,19 +52,37 @@ impl crate::commands::command::Command for Local {
             Err(result) => result?,
         };
         debug!("Use {} as the current version", current_python_version);
-        if !&config
-            .versions_dir()
-            .join(current_python_version.to_string())
-            .exists()
-        {
+        let (installation, resolved) = match &current_python_version {
+            InputVersion::Full(PythonVersion::System) => (
+                system_version::refresh(config)?.ok_or(MamimiError::NoSystemInterpreter)?,
+                None,
+            ),
+            InputVersion::Full(version) => (
+                config.versions_dir().join(version.to_string()),
+                Some(version.clone()),
+            ),
+            // `3.12` or `pypy3.10` in a version file means the newest one installed
+            version => {
+                let installed = installed_versions(config)?;
+                match version.to_version(&installed) {
+                    Some(resolved) => (
+                        config.versions_dir().join(resolved.to_string()),
+                        Some(resolved.clone()),
+                    ),
+                    None => (config.versions_dir().join(version.to_string()), None),
+                }
+            }
+        };
+        if !installation.exists() {
             return Err(MamimiError::VersionNotFound {
                 version: current_python_version,
             });
         }
+        if let Some(version) = &resolved {
+            warn_if_eol(config, version);
+        }
         replace_symlink(
-            &config
-                .versions_dir()
-                .join(current_python_version.to_string()),
+            &installation,
             &config
                 .mamimi_path
                 .clone()
@@ -70,7 +93,7 @@ impl crate::commands::command::Command for Local {
     }
 }
 
-fn replace_symlink(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+pub fn replace_symlink(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
     let symlink_deletion_result = remove_symlink_dir(&to);
     match create_symlink_dir(&from, &to) {
         ok @ Ok(_) => ok,
@@ -0,0 +1,48 @@
+use crate::build_log::find_logs;
+use crate::config::MamimiConfig;
+use crate::python_version::PythonVersion;
+use std::io::Write;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MamimiError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("No build log found for {version}")]
+    LogNotFound { version: PythonVersion },
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct Logs {
+    /// The installed version, e.g. `3.12.4` or `3.12.4-debug`
+    pub version: PythonVersion,
+    /// Print the path of the log instead of its contents
+    #[clap(long)]
+    pub path: bool,
+    /// List every log of the version, oldest first
+    #[clap(long, conflicts_with = "path")]
+    pub all: bool,
+}
+
+impl crate::commands::command::Command for Logs {
+    type Error = MamimiError;
+
+    fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
+        let logs = find_logs(config, &self.version)?;
+        if self.all {
+            for log in &logs {
+                println!("{}", log.display());
+            }
+            return Ok(());
+        }
+        let latest = logs.last().ok_or(MamimiError::LogNotFound {
+            version: self.version,
+        })?;
+        if self.path {
+            println!("{}", latest.display());
+        } else {
+            std::io::stdout().write_all(&std::fs::read(latest)?)?;
+        }
+        Ok(())
+    }
+}
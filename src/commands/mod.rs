@@ -1,9 +1,17 @@
 pub mod command;
 pub mod completions;
+pub mod doctor;
 pub mod global;
+pub mod import;
 pub mod init;
 pub mod install;
 pub mod install_list;
+pub mod link;
 pub mod local;
+pub mod logs;
+pub mod outdated;
+pub mod prune;
 pub mod uninstall;
+pub mod unlink;
+pub mod upgrade;
 pub mod versions;
\ No newline at end of file
@@ -0,0 +1,221 @@
+use crate::config::MamimiConfig;
+use crate::input_version::InputVersion;
+use crate::outln;
+use crate::python_version::{installed_versions, PythonVersion};
+use crate::release_status::{self, ReleaseTable, SupportStatus};
+use chrono::NaiveDate;
+use colored::Colorize;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MamimiError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+    #[error("Can't list the remote versions: {source}")]
+    CannotListRemoteVersions { source: crate::http::Error },
+    #[error("Can't refresh the release status table: {source}")]
+    CannotRefresh { source: release_status::Error },
+    #[error("{count} installed version(s) are outdated or end-of-life")]
+    OutdatedVersions { count: usize },
+}
+
+#[derive(clap::Parser, Debug, Default)]
+pub struct Outdated {
+    /// Print the report as JSON
+    #[clap(long)]
+    pub json: bool,
+    /// Exit with an error when a version has a newer patch or its series is end-of-life
+    #[clap(long)]
+    pub exit_code: bool,
+    /// Download the latest release status table before comparing
+    #[clap(long)]
+    pub refresh: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Row {
+    pub version: PythonVersion,
+    pub latest: Option<PythonVersion>,
+    pub status: SupportStatus,
+    pub eol: Option<NaiveDate>,
+}
+
+impl Row {
+    pub fn has_update(&self) -> bool {
+        matches!(&self.latest, Some(latest) if *latest > self.version.without_profile())
+    }
+
+    fn needs_attention(&self) -> bool {
+        self.has_update() || self.status == SupportStatus::EndOfLife
+    }
+}
+
+impl crate::commands::command::Command for Outdated {
+    type Error = MamimiError;
+
+    fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
+        let table = if self.refresh {
+            ReleaseTable::refresh(config).map_err(|source| MamimiError::CannotRefresh { source })?
+        } else {
+            ReleaseTable::load(config)
+        };
+        let remote = match crate::remote_python_index::list(config) {
+            Ok(remote) => remote.into_iter().map(|x| x.python_version).collect(),
+            Err(source) => {
+                let cached = crate::remote_python_index::read_cache(config);
+                if cached.is_empty() {
+                    return Err(MamimiError::CannotListRemoteVersions { source });
+                }
+                outln!(
+                    config,
+                    Error,
+                    "{} can't list the remote versions, using the cached list: {}",
+                    "warning:".yellow().bold(),
+                    source
+                );
+                cached
+            }
+        };
+        let rows = rows(
+            &installed_versions(config)?,
+            &remote,
+            &table,
+            release_status::today(),
+        );
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        } else {
+            print_table(&rows);
+        }
+        let count = rows.iter().filter(|row| row.needs_attention()).count();
+        if self.exit_code && count > 0 {
+            return Err(MamimiError::OutdatedVersions { count });
+        }
+        Ok(())
+    }
+}
+
+pub fn rows(
+    installed: &[PythonVersion],
+    remote: &[PythonVersion],
+    table: &ReleaseTable,
+    today: NaiveDate,
+) -> Vec<Row> {
+    installed
+        .iter()
+        .filter_map(|version| {
+            let series = match version {
+                PythonVersion::Semver(v) => InputVersion::MajorMinor(v.major, v.minor),
+                // Only CPython has a support schedule to compare against
+                PythonVersion::System | PythonVersion::Alternative(_) => return None,
+            };
+            Some(Row {
+                version: version.clone(),
+                latest: series.to_version(remote).cloned(),
+                status: table.status(version, today),
+                eol: table.eol_date(version),
+            })
+        })
+        .collect()
+}
+
+fn print_table(rows: &[Row]) {
+    let cells = rows
+        .iter()
+        .map(|row| {
+            [
+                row.version.to_string(),
+                row.latest
+                    .as_ref()
+                    .map_or_else(|| "-".to_string(), ToString::to_string),
+                row.status.to_string(),
+                row.eol
+                    .map_or_else(|| "-".to_string(), |date| date.to_string()),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let headers = ["VERSION", "LATEST", "STATUS", "EOL"];
+    let widths = (0..headers.len())
+        .map(|column| {
+            cells
+                .iter()
+                .map(|row| row[column].len())
+                .chain(std::iter::once(headers[column].len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+    let pad = |column: usize, value: &str| format!("{:width$}", value, width = widths[column]);
+
+    println!(
+        "{}",
+        headers
+            .iter()
+            .enumerate()
+            .map(|(column, header)| pad(column, header))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .bold()
+    );
+    for (row, cells) in rows.iter().zip(&cells) {
+        let latest = pad(1, &cells[1]);
+        let latest = if row.has_update() {
+            latest.yellow()
+        } else {
+            latest.normal()
+        };
+        let status = pad(2, &cells[2]);
+        let status = match row.status {
+            SupportStatus::EndOfLife => status.red(),
+            SupportStatus::Security => status.yellow(),
+            SupportStatus::Bugfix => status.green(),
+            SupportStatus::Unknown => status.normal(),
+        };
+        println!(
+            "{}  {}  {}  {}",
+            pad(0, &cells[0]),
+            latest,
+            status,
+            cells[3]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows() {
+        let v = |version: &str| PythonVersion::parse(version).unwrap();
+        let installed = [v("3.7.9"), v("3.11.6"), v("3.12.4-debug"), v("3.12.4")];
+        let remote = [v("3.7.17"), v("3.11.6"), v("3.12.3"), v("3.12.4")];
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let rows = rows(&installed, &remote, &ReleaseTable::embedded(), today);
+
+        let summary = rows
+            .iter()
+            .map(|row| (row.version.to_string(), row.has_update(), row.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("3.7.9".to_string(), true, SupportStatus::EndOfLife),
+                ("3.11.6".to_string(), false, SupportStatus::Security),
+                ("3.12.4-debug".to_string(), false, SupportStatus::Bugfix),
+                ("3.12.4".to_string(), false, SupportStatus::Bugfix),
+            ]
+        );
+        assert_eq!(rows.iter().filter(|row| row.needs_attention()).count(), 1);
+
+        let json = serde_json::to_value(&rows[0]).unwrap();
+        assert_eq!(json["latest"], "3.7.17");
+        assert_eq!(json["status"], "end-of-life");
+        assert_eq!(json["eol"], "2023-06-27");
+    }
+}
@@ -0,0 +1,239 @@
+use crate::alias::list_aliases;
+use crate::config::MamimiConfig;
+use crate::current_python_version::current_python_version;
+use crate::disk_usage::{disk_usage, format_size};
+use crate::lock::Lock;
+use crate::outln;
+use crate::python_version::{installed_versions, PythonVersion};
+use crate::venv::find_venvs;
+use crate::version_files::{find_version_files, get_user_version_for_file};
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MamimiError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Pruning was cancelled")]
+    Cancelled,
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct Prune {
+    /// How many of the newest patches to keep in every minor series
+    #[clap(long, default_value_t = 1)]
+    pub keep: usize,
+    /// Keep the versions used by `.python-version` files and virtualenvs under
+    /// this directory. Can be given several times
+    #[clap(long = "root", value_name = "DIR")]
+    pub roots: Vec<PathBuf>,
+    /// Remove without asking for confirmation
+    #[clap(long, short = 'y')]
+    pub yes: bool,
+    /// Print what would be removed without removing anything
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+/// Why a superseded version can't be removed.
+type References = BTreeMap<PythonVersion, Vec<String>>;
+
+impl crate::commands::command::Command for Prune {
+    type Error = MamimiError;
+
+    fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
+        let installed = installed_versions(config)?;
+        let superseded = superseded(&installed, self.keep);
+        let references = self.references(config, &installed)?;
+
+        let mut removed = vec![];
+        for version in &superseded {
+            match references.get(version) {
+                Some(reasons) => println!(
+                    "{} {} (used by {})",
+                    "keep".green(),
+                    version,
+                    reasons.join(", ")
+                ),
+                None => {
+                    let path = config.versions_dir().join(version.to_string());
+                    let size = disk_usage(&path);
+                    println!("{} {} ({})", "remove".red(), version, format_size(size));
+                    removed.push((version, path, size));
+                }
+            }
+        }
+        let total = removed.iter().map(|(_, _, size)| size).sum::<u64>();
+        if removed.is_empty() {
+            outln!(config, Error, "{} Nothing to prune", "==>".green());
+            return Ok(());
+        }
+        if self.dry_run {
+            println!("Would reclaim {}", format_size(total));
+            return Ok(());
+        }
+        if !self.yes && !crate::prompt::confirm(&format!("Remove {} version(s)?", removed.len()))? {
+            return Err(MamimiError::Cancelled);
+        }
+        for (version, path, _) in &removed {
+            let _lock = Lock::version(config, version)?;
+            std::fs::remove_dir_all(path)?;
+        }
+        outln!(
+            config,
+            Error,
+            "{} Removed {} version(s), reclaimed {}",
+            "==>".green(),
+            removed.len(),
+            format_size(total)
+        );
+        Ok(())
+    }
+}
+
+impl Prune {
+    /// Every installed version that something still points at.
+    fn references(
+        &self,
+        config: &MamimiConfig,
+        installed: &[PythonVersion],
+    ) -> Result<References, MamimiError> {
+        let mut references = References::new();
+        let mut add = |version: PythonVersion, reason: String| {
+            references.entry(version).or_default().push(reason);
+        };
+        if let Some(version) = current_python_version(config).ok().flatten() {
+            add(version, "the current shell".to_string());
+        }
+        match list_aliases(config) {
+            Ok(aliases) => {
+                for alias in aliases {
+                    if let Ok(version) = PythonVersion::parse(alias.s_ver()) {
+                        add(version, format!("alias {}", alias.name()));
+                    }
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        for root in &self.roots {
+            for path in find_version_files(root)? {
+                let version = get_user_version_for_file(path.clone())
+                    .and_then(|input| input.to_version(installed).cloned());
+                if let Some(version) = version {
+                    add(version, path.display().to_string());
+                }
+            }
+            for venv in find_venvs(root) {
+                let used = installed.iter().find(|version| {
+                    version
+                        .installation_path(config)
+                        .map_or(false, |path| venv.uses(&path))
+                });
+                if let Some(version) = used {
+                    add(version.clone(), venv.path.display().to_string());
+                }
+            }
+        }
+        Ok(references)
+    }
+}
+
+/// The versions older than the `keep` newest patches of their minor series.
+/// Builds with a profile, such as `3.12.4-debug`, form series of their own.
+fn superseded(installed: &[PythonVersion], keep: usize) -> Vec<PythonVersion> {
+    let mut series: BTreeMap<_, Vec<&PythonVersion>> = BTreeMap::new();
+    for version in installed {
+        if let PythonVersion::Semver(v) = version {
+            series
+                .entry((v.major, v.minor, v.pre.clone()))
+                .or_default()
+                .push(version);
+        }
+    }
+    let mut superseded = series
+        .into_values()
+        .flat_map(|mut versions| {
+            versions.sort_by(|a, b| b.cmp(a));
+            versions.into_iter().skip(keep).cloned()
+        })
+        .collect::<Vec<_>>();
+    superseded.sort();
+    superseded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alias::create_alias;
+
+    fn versions(versions: &[&str]) -> Vec<PythonVersion> {
+        versions
+            .iter()
+            .map(|version| PythonVersion::parse(version).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_superseded() {
+        let installed = versions(&[
+            "3.10.2",
+            "3.10.4",
+            "3.10.8",
+            "3.11.1-debug",
+            "3.11.6",
+            "3.12.0",
+        ]);
+        assert_eq!(superseded(&installed, 1), versions(&["3.10.2", "3.10.4"]));
+        assert_eq!(superseded(&installed, 2), versions(&["3.10.2"]));
+    }
+
+    #[test]
+    fn test_references() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let installed = versions(&["3.10.2", "3.10.4", "3.10.8", "3.9.1", "3.9.2"]);
+        for version in &installed {
+            std::fs::create_dir_all(config.versions_dir().join(version.to_string()).join("bin"))
+                .unwrap();
+        }
+        create_alias(&config, "default", &installed[0]).unwrap();
+
+        let projects = tempfile::tempdir().unwrap();
+        let app = projects.path().join("app");
+        std::fs::create_dir_all(app.join(".venv")).unwrap();
+        std::fs::write(app.join(".python-version"), "3.9\n").unwrap();
+        std::fs::write(
+            app.join(".venv/pyvenv.cfg"),
+            format!(
+                "home = {}\n",
+                config.versions_dir().join("3.10.4/bin").display()
+            ),
+        )
+        .unwrap();
+
+        let prune = Prune {
+            keep: 1,
+            roots: vec![projects.path().to_path_buf()],
+            yes: true,
+            dry_run: true,
+        };
+        let references = prune.references(&config, &installed).unwrap();
+        let reasons = |version: &str| references.get(&PythonVersion::parse(version).unwrap());
+        assert_eq!(
+            reasons("3.10.2").unwrap(),
+            &vec!["alias default".to_string()]
+        );
+        assert_eq!(
+            reasons("3.10.4").unwrap(),
+            &vec![app.join(".venv").display().to_string()]
+        );
+        assert_eq!(
+            reasons("3.9.2").unwrap(),
+            &vec![app.join(".python-version").display().to_string()]
+        );
+        assert!(reasons("3.9.1").is_none());
+    }
+}
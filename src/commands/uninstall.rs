@@ -1,12 +1,12 @@
+use crate::alias::{create_alias, list_aliases};
 use crate::config::MamimiConfig;
 use crate::input_version::InputVersion;
+use crate::lock::Lock;
 use crate::outln;
-use crate::python_version::PythonVersion;
+use crate::python_version::{installed_versions, PythonVersion};
 use crate::symlink::remove_symlink_dir;
 use colored::Colorize;
 use log::debug;
-use std::ffi::OsStr;
-use std::path::Component;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -22,69 +22,278 @@ pub enum MamimiError {
     VersionNotFound { version: InputVersion },
     #[error("The reqwested version is not installable: {version}")]
     NotInstallableVersion { version: PythonVersion },
-    #[error("We can't find the necessary envitonment to replace the Python version.")]
-    MamimipathNotFound,
+    #[error("{version} is active in the current shell. Pass --force to remove it anyway.")]
+    VersionInUse { version: PythonVersion },
+    #[error("Uninstalling was cancelled")]
+    Cancelled,
 }
 
 #[derive(clap::Parser, Debug)]
 pub struct Uninstall {
-    version: InputVersion,
+    /// The version to remove. A partial version such as `3.10` removes every
+    /// installed version it matches
+    pub version: InputVersion,
+    /// Keep the newest of the matching versions
+    #[clap(long)]
+    pub all_but_latest: bool,
+    /// Remove the version even if it is active in the current shell
+    #[clap(long)]
+    pub force: bool,
+    /// Don't ask for confirmation
+    #[clap(long, short = 'y')]
+    pub yes: bool,
 }
 
 impl crate::commands::command::Command for Uninstall {
     type Error = MamimiError;
 
     fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
-        let current_version = self.version.clone();
-        let version = match current_version.clone() {
-            InputVersion::Full(PythonVersion::Semver(v)) => PythonVersion::Semver(v),
-            InputVersion::Full(PythonVersion::System) => {
-                return Err(MamimiError::NotInstallableVersion {
-                    version: PythonVersion::System,
-                })
-            }
-            _ => unreachable!(),
-        };
-        let installation_dir = PathBuf::from(&config.versions_dir()).join(version.to_string());
-        if !installation_dir.exists() {
+        if let InputVersion::Full(PythonVersion::System) = self.version {
+            return Err(MamimiError::NotInstallableVersion {
+                version: PythonVersion::System,
+            });
+        }
+        let mut targets = installed_versions(config)?
+            .into_iter()
+            .filter(|version| self.version.matches(version))
+            .collect::<Vec<_>>();
+        if targets.is_empty() {
             return Err(MamimiError::VersionNotFound {
-                version: current_version,
+                version: self.version,
             });
         }
-        outln!(
-            config,
-            Error,
-            "{} Uninstalling {}",
-            "==>".green(),
-            format!("Python {}", current_version).green()
-        );
-        if symlink_exists(
-            config
-                .mamimi_path
-                .clone()
-                .ok_or(MamimiError::MamimipathNotFound)?,
-            &version,
-        )? {
-            debug!("remove mamimi path symlink");
-            remove_symlink_dir(
-                &config
-                    .mamimi_path
-                    .clone()
-                    .ok_or(MamimiError::MamimipathNotFound)?,
-            )?;
+        if self.all_but_latest {
+            let latest = targets.pop().expect("targets is not empty");
+            if targets.is_empty() {
+                outln!(
+                    config,
+                    Error,
+                    "{} Nothing to uninstall, {} is the only {} installed",
+                    "==>".green(),
+                    latest,
+                    self.version
+                );
+                return Ok(());
+            }
+        }
+        if !self.force {
+            if let Some(version) = targets.iter().find(|version| is_active(config, version)) {
+                return Err(MamimiError::VersionInUse {
+                    version: version.clone(),
+                });
+            }
         }
-        if symlink_exists(config.default_python_version_dir(), &version)? {
-            debug!("rmeove default alias symlink");
-            remove_symlink_dir(&config.default_python_version_dir())?;
+        let exact = matches!(self.version, InputVersion::Full(_)) && targets.len() == 1;
+        if !exact && !self.yes {
+            let names = targets.iter().map(ToString::to_string).collect::<Vec<_>>();
+            let question = format!("Uninstall {}?", names.join(", "));
+            if !crate::prompt::confirm(&question)? {
+                return Err(MamimiError::Cancelled);
+            }
+        }
+
+        // Aliases can't be resolved anymore once their target is gone
+        let aliases = match list_aliases(config) {
+            Ok(aliases) => aliases
+                .into_iter()
+                .filter_map(|alias| {
+                    let version = PythonVersion::parse(alias.s_ver()).ok()?;
+                    targets.contains(&version).then(|| {
+                        (
+                            alias.name().to_string(),
+                            alias.path().to_path_buf(),
+                            version,
+                        )
+                    })
+                })
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err.into()),
+        };
+        for version in &targets {
+            remove(config, version)?;
         }
-        debug!("remove dir");
-        std::fs::remove_dir_all(&installation_dir)?;
+        reassign_aliases(config, aliases)?;
         Ok(())
     }
 }
 
-fn symlink_exists(to: PathBuf, version: &PythonVersion) -> Result<bool, MamimiError> {
-    debug!("symlink exists?");
-    Ok(std::fs::read_link(to)?.components().last()
-        == Some(Component::Normal(OsStr::new(&version.to_string()))))
+fn remove(config: &MamimiConfig, version: &PythonVersion) -> Result<(), MamimiError> {
+    let _lock = Lock::version(config, version)?;
+    outln!(
+        config,
+        Error,
+        "{} Uninstalling {}",
+        "==>".green(),
+        format!("Python {}", version).green()
+    );
+    if let Some(mamimi_path) = &config.mamimi_path {
+        if is_active(config, version) {
+            debug!("remove mamimi path symlink");
+            remove_symlink_dir(mamimi_path)?;
+        }
+    }
+    debug!("remove dir");
+    std::fs::remove_dir_all(config.versions_dir().join(version.to_string()))?;
+    Ok(())
+}
+
+/// Points `default` at the newest remaining version, preferring the series of
+/// the removed one, and removes the other aliases of removed versions.
+fn reassign_aliases(
+    config: &MamimiConfig,
+    aliases: Vec<(String, PathBuf, PythonVersion)>,
+) -> Result<(), MamimiError> {
+    if aliases.is_empty() {
+        return Ok(());
+    }
+    let _lock = Lock::aliases(config)?;
+    let remaining = installed_versions(config)?;
+    for (name, path, removed) in aliases {
+        let replacement = match removed {
+            PythonVersion::Semver(ref removed) if name == "default" => {
+                InputVersion::MajorMinor(removed.major, removed.minor)
+                    .to_version(&remaining)
+                    .or_else(|| remaining.iter().rev().find(|v| v.profile().is_none()))
+            }
+            _ => None,
+        };
+        match replacement {
+            Some(version) => {
+                create_alias(config, &name, version)?;
+                outln!(
+                    config,
+                    Error,
+                    "{} {} now points to {}",
+                    "==>".green(),
+                    name,
+                    version
+                );
+            }
+            None => {
+                remove_symlink_dir(&path)?;
+                outln!(
+                    config,
+                    Error,
+                    "{} Removed the alias {}",
+                    "==>".green(),
+                    name
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether the current shell uses `version`, also through an alias such as
+/// `default` which the multishell path points to after `mamimi init`.
+pub fn is_active(config: &MamimiConfig, version: &PythonVersion) -> bool {
+    let multishell = match &config.mamimi_path {
+        Some(path) => path,
+        None => return false,
+    };
+    let installation = config.versions_dir().join(version.to_string());
+    match (multishell.canonicalize(), installation.canonicalize()) {
+        (Ok(active), Ok(installation)) => active == installation,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::command::Command;
+    use crate::symlink::create_symlink_dir;
+
+    fn setup(versions: &[&str]) -> (tempfile::TempDir, MamimiConfig) {
+        let base_dir = tempfile::tempdir().unwrap();
+        let mut config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        for version in versions {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+        config.mamimi_path = Some(base_dir.path().join("multishell"));
+        (base_dir, config)
+    }
+
+    #[test]
+    fn test_keeps_the_latest_and_reassigns_default() {
+        let (_base_dir, config) = setup(&["3.10.2", "3.10.8", "3.11.6"]);
+        let v = |version: &str| PythonVersion::parse(version).unwrap();
+        create_alias(&config, "default", &v("3.10.2")).unwrap();
+        create_alias(&config, "work", &v("3.10.2")).unwrap();
+
+        Uninstall {
+            version: "3.10".parse().unwrap(),
+            all_but_latest: true,
+            force: false,
+            yes: true,
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert_eq!(
+            installed_versions(&config).unwrap(),
+            vec![v("3.10.8"), v("3.11.6")]
+        );
+        let aliases = list_aliases(&config).unwrap();
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(aliases[0].name(), "default");
+        assert_eq!(aliases[0].s_ver(), "3.10.8");
+    }
+
+    #[test]
+    fn test_refuses_the_active_version() {
+        let (_base_dir, config) = setup(&["3.11.6"]);
+        let mamimi_path = config.mamimi_path.clone().unwrap();
+        create_symlink_dir(config.versions_dir().join("3.11.6"), &mamimi_path).unwrap();
+        let uninstall = |force| Uninstall {
+            version: "3.11.6".parse().unwrap(),
+            all_but_latest: false,
+            force,
+            yes: false,
+        };
+
+        assert!(matches!(
+            uninstall(false).apply(&config),
+            Err(MamimiError::VersionInUse { .. })
+        ));
+        uninstall(true).apply(&config).unwrap();
+        assert!(installed_versions(&config).unwrap().is_empty());
+        assert!(std::fs::symlink_metadata(&mamimi_path).is_err());
+    }
+
+    #[test]
+    fn test_refuses_the_active_version_through_an_alias() {
+        let (_base_dir, config) = setup(&["3.11.6"]);
+        let v = |version: &str| PythonVersion::parse(version).unwrap();
+        create_alias(&config, "default", &v("3.11.6")).unwrap();
+        // What `mamimi init` sets up
+        let mamimi_path = config.mamimi_path.clone().unwrap();
+        create_symlink_dir(config.default_python_version_dir(), &mamimi_path).unwrap();
+
+        let result = Uninstall {
+            version: "3.11.6".parse().unwrap(),
+            all_but_latest: false,
+            force: false,
+            yes: false,
+        }
+        .apply(&config);
+        assert!(matches!(result, Err(MamimiError::VersionInUse { .. })));
+        assert_eq!(installed_versions(&config).unwrap(), vec![v("3.11.6")]);
+    }
+
+    #[test]
+    fn test_works_without_a_shell() {
+        let (_base_dir, mut config) = setup(&["3.9.6"]);
+        config.mamimi_path = None;
+        Uninstall {
+            version: "3.9.6".parse().unwrap(),
+            all_but_latest: false,
+            force: false,
+            yes: false,
+        }
+        .apply(&config)
+        .unwrap();
+        assert!(installed_versions(&config).unwrap().is_empty());
+    }
 }
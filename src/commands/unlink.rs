@@ -0,0 +1,66 @@
+use crate::commands::uninstall::{self, Uninstall};
+use crate::config::MamimiConfig;
+use crate::input_version::InputVersion;
+use crate::install_metadata::{InstallMetadata, Origin};
+use crate::python_version::{installed_versions, PythonVersion};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MamimiError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    UninstallError(#[from] uninstall::MamimiError),
+    #[error("Can't find version: {version}")]
+    VersionNotFound { version: InputVersion },
+    #[error("{version} wasn't linked. Use `mamimi uninstall` to remove it")]
+    NotLinked { version: PythonVersion },
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct Unlink {
+    /// The name of a linked interpreter, e.g. `3.12.4-opt` or `3.12-opt`
+    pub version: InputVersion,
+    /// Unlink it even if it is active in the current shell
+    #[clap(long)]
+    pub force: bool,
+}
+
+impl crate::commands::command::Command for Unlink {
+    type Error = MamimiError;
+
+    fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
+        let version = match &self.version {
+            InputVersion::Full(version) => Some(version.clone()),
+            input => input.to_version(&installed_versions(config)?).cloned(),
+        };
+        let version = match version {
+            Some(version) => version,
+            None => {
+                return Err(MamimiError::VersionNotFound {
+                    version: self.version,
+                })
+            }
+        };
+        let entry = config.versions_dir().join(version.to_string());
+        if std::fs::symlink_metadata(&entry).is_err() {
+            return Err(MamimiError::VersionNotFound {
+                version: self.version,
+            });
+        }
+        let linked = InstallMetadata::read(&entry)
+            .map_or(false, |metadata| metadata.origin == Origin::Linked);
+        if !linked {
+            return Err(MamimiError::NotLinked { version });
+        }
+        // Removing the entry only removes its symlinks, never the linked interpreter
+        Uninstall {
+            version: InputVersion::Full(version),
+            all_but_latest: false,
+            force: self.force,
+            yes: true,
+        }
+        .apply(config)?;
+        Ok(())
+    }
+}
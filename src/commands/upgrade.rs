@@ -0,0 +1,328 @@
+use crate::alias::{create_alias, list_aliases};
+use crate::commands::command::Command;
+use crate::commands::local::replace_symlink;
+use crate::commands::{install, uninstall};
+use crate::config::MamimiConfig;
+use crate::input_version::InputVersion;
+use crate::lock::Lock;
+use crate::outln;
+use crate::python_version::{installed_versions, PythonVersion};
+use crate::version_files::{find_version_files, get_user_version_for_file};
+use clap::Parser;
+use colored::Colorize;
+use log::debug;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MamimiError {
+    #[error(transparent)]
+    HttpError(#[from] crate::http::Error),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    InstallError(#[from] install::MamimiError),
+    #[error(transparent)]
+    UninstallError(#[from] uninstall::MamimiError),
+    #[error("Upgrade takes a series such as 3.11, not {version}")]
+    NotASeries { version: InputVersion },
+    #[error("Can't find any {series} release")]
+    NoReleases { series: InputVersion },
+}
+
+#[derive(clap::Parser, Debug)]
+pub struct Upgrade {
+    /// The series to move to its latest patch release, e.g. `3.11`
+    pub series: InputVersion,
+    /// Also rewrite the `.python-version` files under this directory that pin
+    /// a superseded patch
+    #[clap(long, value_name = "DIR")]
+    pub rewrite_version_files: Option<PathBuf>,
+    /// Uninstall the superseded patches without asking
+    #[clap(long, conflicts_with = "keep_old")]
+    pub uninstall_old: bool,
+    /// Keep the superseded patches without asking
+    #[clap(long)]
+    pub keep_old: bool,
+    /// Print what would change without changing anything
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+/// Everything an upgrade changes.
+#[derive(Debug, PartialEq)]
+struct Plan {
+    latest: PythonVersion,
+    install: bool,
+    superseded: Vec<PythonVersion>,
+    aliases: Vec<(String, PythonVersion)>,
+    version_files: Vec<(PathBuf, PythonVersion)>,
+}
+
+impl std::fmt::Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.install {
+            writeln!(f, "    install {}", self.latest)?;
+        }
+        for (name, from) in &self.aliases {
+            writeln!(f, "    alias {}: {} -> {}", name, from, self.latest)?;
+        }
+        for (path, from) in &self.version_files {
+            writeln!(
+                f,
+                "    rewrite {}: {} -> {}",
+                path.display(),
+                from,
+                self.latest
+            )?;
+        }
+        for version in &self.superseded {
+            writeln!(f, "    superseded {}", version)?;
+        }
+        Ok(())
+    }
+}
+
+impl Command for Upgrade {
+    type Error = MamimiError;
+
+    fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
+        let remote = crate::remote_python_index::list(config)?
+            .into_iter()
+            .map(|x| x.python_version)
+            .collect::<Vec<_>>();
+        let plan = self.plan(config, &remote)?;
+        if self.dry_run {
+            println!("Upgrading {} to {} would:", self.series, plan.latest);
+            print!("{}", plan);
+            return Ok(());
+        }
+        if !plan.install && plan.superseded.is_empty() {
+            outln!(
+                config,
+                Error,
+                "{} {} is already the latest {} release",
+                "==>".green(),
+                plan.latest,
+                self.series
+            );
+            return Ok(());
+        }
+        outln!(
+            config,
+            Error,
+            "{} Upgrading {} to {}\n{}",
+            "==>".green(),
+            self.series,
+            plan.latest,
+            plan.to_string().trim_end()
+        );
+
+        if plan.install {
+            let mut install = install::Install::parse_from(["install"]);
+            install.versions = vec![InputVersion::Full(plan.latest.clone())];
+            install.apply(config)?;
+        }
+        if !plan.aliases.is_empty() {
+            let _lock = Lock::aliases(config)?;
+            for (name, _) in &plan.aliases {
+                create_alias(config, name, &plan.latest)?;
+            }
+        }
+        for (path, _) in &plan.version_files {
+            std::fs::write(path, format!("{}\n", plan.latest))?;
+        }
+
+        self.uninstall_superseded(config, &plan)
+    }
+}
+
+impl Upgrade {
+    fn uninstall_superseded(&self, config: &MamimiConfig, plan: &Plan) -> Result<(), MamimiError> {
+        for version in &plan.superseded {
+            let uninstall = self.uninstall_old
+                || (!self.keep_old
+                    && crate::prompt::confirm(&format!("Uninstall the superseded {}?", version))?);
+            if !uninstall {
+                continue;
+            }
+            // Move the current shell along instead of leaving it without a Python
+            if uninstall::is_active(config, version) {
+                if let Some(multishell) = &config.mamimi_path {
+                    let latest = config.versions_dir().join(plan.latest.to_string());
+                    if let Err(err) = replace_symlink(&latest, multishell) {
+                        debug!("Can't switch the current shell to {}: {}", plan.latest, err);
+                    }
+                }
+            }
+            let result = uninstall::Uninstall {
+                version: InputVersion::Full(version.clone()),
+                all_but_latest: false,
+                force: false,
+                yes: true,
+            }
+            .apply(config);
+            match result {
+                Ok(()) => {}
+                Err(uninstall::MamimiError::VersionInUse { version }) => outln!(
+                    config,
+                    Error,
+                    "{} Keeping {}, it is still active in the current shell",
+                    "warning:".yellow().bold(),
+                    version
+                ),
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn plan(&self, config: &MamimiConfig, remote: &[PythonVersion]) -> Result<Plan, MamimiError> {
+        let series = match &self.series {
+            InputVersion::MajorMinor(..) => self.series.clone(),
+            InputVersion::Full(PythonVersion::Semver(v)) => {
+                InputVersion::MajorMinor(v.major, v.minor)
+            }
+            version => {
+                return Err(MamimiError::NotASeries {
+                    version: version.clone(),
+                })
+            }
+        };
+        let latest = series
+            .to_version(remote)
+            .ok_or_else(|| MamimiError::NoReleases {
+                series: series.clone(),
+            })?
+            .clone();
+        let installed = installed_versions(config)?;
+        let superseded = installed
+            .iter()
+            .filter(|version| series.matches(version) && **version < latest)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let aliases = match list_aliases(config) {
+            Ok(aliases) => aliases,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err.into()),
+        };
+        let aliases = aliases
+            .iter()
+            .filter_map(|alias| {
+                let target = PythonVersion::parse(alias.s_ver()).ok()?;
+                superseded
+                    .contains(&target)
+                    .then(|| (alias.name().to_string(), target))
+            })
+            .collect();
+
+        let mut version_files = vec![];
+        if let Some(root) = &self.rewrite_version_files {
+            for path in find_version_files(root)? {
+                // Files naming the series itself keep working and are left alone
+                if let Some(InputVersion::Full(version)) = get_user_version_for_file(path.clone()) {
+                    if superseded.contains(&version) {
+                        version_files.push((path, version));
+                    }
+                }
+            }
+        }
+
+        Ok(Plan {
+            install: !installed.contains(&latest),
+            latest,
+            superseded,
+            aliases,
+            version_files,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        for version in ["3.10.1", "3.11.6", "3.11.6-debug"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+        let v = |version: &str| PythonVersion::parse(version).unwrap();
+        create_alias(&config, "default", &v("3.11.6")).unwrap();
+        create_alias(&config, "old", &v("3.10.1")).unwrap();
+
+        let projects = tempfile::tempdir().unwrap();
+        for (dir, contents) in [("a", "3.11.6\n"), ("b/c", "3.11"), ("d", "3.10.1")] {
+            let dir = projects.path().join(dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join(".python-version"), contents).unwrap();
+        }
+
+        let upgrade = Upgrade {
+            series: "3.11".parse().unwrap(),
+            rewrite_version_files: Some(projects.path().to_path_buf()),
+            uninstall_old: false,
+            keep_old: false,
+            dry_run: true,
+        };
+        let remote = [v("3.11.6"), v("3.11.9"), v("3.12.0")];
+        assert_eq!(
+            upgrade.plan(&config, &remote).unwrap(),
+            Plan {
+                latest: v("3.11.9"),
+                install: true,
+                superseded: vec![v("3.11.6")],
+                aliases: vec![("default".to_string(), v("3.11.6"))],
+                version_files: vec![(projects.path().join("a/.python-version"), v("3.11.6"))],
+            }
+        );
+
+        let upgrade = Upgrade {
+            series: "3".parse().unwrap(),
+            ..upgrade
+        };
+        assert!(matches!(
+            upgrade.plan(&config, &remote),
+            Err(MamimiError::NotASeries { .. })
+        ));
+    }
+
+    #[test]
+    fn test_uninstall_the_active_version() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let mut config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        for version in ["3.11.6", "3.11.9"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+        let multishell = base_dir.path().join("multishell");
+        crate::symlink::create_symlink_dir(config.versions_dir().join("3.11.6"), &multishell)
+            .unwrap();
+        config.mamimi_path = Some(multishell.clone());
+        let v = |version: &str| PythonVersion::parse(version).unwrap();
+
+        let upgrade = Upgrade {
+            series: "3.11".parse().unwrap(),
+            rewrite_version_files: None,
+            uninstall_old: true,
+            keep_old: false,
+            dry_run: false,
+        };
+        let plan = Plan {
+            latest: v("3.11.9"),
+            install: false,
+            superseded: vec![v("3.11.6")],
+            aliases: vec![],
+            version_files: vec![],
+        };
+        upgrade.uninstall_superseded(&config, &plan).unwrap();
+
+        assert_eq!(installed_versions(&config).unwrap(), vec![v("3.11.9")]);
+        assert_eq!(
+            std::fs::read_link(&multishell).unwrap(),
+            config.versions_dir().join("3.11.9")
+        );
+    }
+}
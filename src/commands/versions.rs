@@ -1,9 +1,15 @@
+use crate::alias::list_aliases;
 use crate::config::MamimiConfig;
 use crate::current_python_version::current_python_version;
-use crate::outln;
-use crate::python_version::PythonVersion;
+use crate::disk_usage::{disk_usage, format_size};
+use crate::install_metadata::{InstallMetadata, Origin};
+use crate::python_version::{installed_versions, PythonVersion};
+use crate::system_version::{self, discover, SystemInterpreter};
+use crate::version_files::{find_up, get_user_version_for_file, VERSION_FILE};
 use colored::Colorize;
 use log::debug;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,55 +20,289 @@ pub enum MamimiError {
     IoError(#[from] std::io::Error),
     #[error(transparent)]
     SemverError(#[from] semver::Error),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
 }
 
-#[derive(clap::Parser, Debug)]
-pub struct Versions {}
+#[derive(clap::Parser, Debug, Default)]
+pub struct Versions {
+    /// Print the versions as JSON
+    #[clap(long, conflicts_with = "bare")]
+    pub json: bool,
+    /// Print only the version names, one per line
+    #[clap(long)]
+    pub bare: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct InstalledVersion {
+    pub version: PythonVersion,
+    pub path: PathBuf,
+    pub current: bool,
+    /// The version file or alias that makes this version current.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_because: Option<String>,
+    pub aliases: Vec<String>,
+    pub origin: Option<Origin>,
+    pub installed_at: Option<String>,
+    pub size: u64,
+    /// The version a system interpreter reports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub python_version: Option<PythonVersion>,
+}
 
 impl crate::commands::command::Command for Versions {
     type Error = MamimiError;
 
     fn apply(self, config: &MamimiConfig) -> Result<(), Self::Error> {
-        for entry in config
-            .versions_dir()
-            .read_dir()
-            .map_err(MamimiError::IoError)?
-        {
-            let entry = entry.map_err(MamimiError::IoError)?;
-            if crate::python_version::is_dotfile(&entry) {
-                continue;
+        let versions = installed_versions(config)?;
+        // Scripts and completions call this, so don't run every interpreter
+        if self.bare {
+            for version in versions {
+                println!("{}", version);
             }
-
-            let path = entry.path();
-            let filename = path
-                .file_name()
-                .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
-                .map_err(MamimiError::IoError)?
-                .to_str()
-                .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
-                .map_err(MamimiError::IoError)?;
-            let version = PythonVersion::parse(filename).map_err(MamimiError::SemverError)?;
-            let current_python_version = current_python_version(&config).ok().flatten();
-            debug!(
-                "Current Python Version: {}",
-                current_python_version.clone().unwrap()
-            );
-            if let Some(current_python_version) = current_python_version {
-                if current_python_version == version {
-                    outln!(
-                        config,
-                        Error,
-                        "{} {}",
-                        "*".green(),
-                        version.to_string().green()
-                    );
-                } else {
-                    outln!(config, Error, "{} {}", " ", version);
-                }
-            } else {
-                outln!(config, Error, "{} {}", " ", version);
-            };
+            if system_version::is_available(config) {
+                println!("{}", PythonVersion::System);
+            }
+            return Ok(());
+        }
+        let system = discover(config);
+        let current = current_python_version(config).ok().flatten();
+        debug!("Current Python Version: {:?}", current);
+        let mut rows = describe(config, &versions, current.as_ref())?;
+        rows.extend(describe_system(config, &system, current.as_ref())?);
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        } else {
+            print_table(&rows);
         }
         Ok(())
     }
 }
+
+pub fn describe(
+    config: &MamimiConfig,
+    versions: &[PythonVersion],
+    current: Option<&PythonVersion>,
+) -> Result<Vec<InstalledVersion>, MamimiError> {
+    let aliases = match list_aliases(config) {
+        Ok(aliases) => aliases,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(err) => return Err(err.into()),
+    };
+    let aliases_of = |version: &PythonVersion| {
+        aliases
+            .iter()
+            .filter(|alias| alias.s_ver() == version.to_string())
+            .map(|alias| alias.name().to_string())
+            .collect::<Vec<_>>()
+    };
+    let rows = versions
+        .iter()
+        .map(|version| {
+            let path = config.versions_dir().join(version.to_string());
+            let metadata = InstallMetadata::read(&path);
+            let is_current = current == Some(version);
+            let aliases = aliases_of(version);
+            InstalledVersion {
+                current_because: is_current
+                    .then(|| current_because(versions, version, &aliases))
+                    .flatten(),
+                version: version.clone(),
+                current: is_current,
+                aliases,
+                origin: metadata.as_ref().map(|metadata| metadata.origin),
+                installed_at: metadata
+                    .map(|metadata| metadata.installed_at)
+                    .or_else(|| modified_at(&path)),
+                size: disk_usage(&path),
+                path,
+                python_version: None,
+            }
+        })
+        .collect();
+    Ok(rows)
+}
+
+/// The interpreters `system` stands for. Only the first one, which `python3`
+/// runs, is current when `system` is.
+pub fn describe_system(
+    config: &MamimiConfig,
+    interpreters: &[SystemInterpreter],
+    current: Option<&PythonVersion>,
+) -> Result<Vec<InstalledVersion>, MamimiError> {
+    let aliases = match list_aliases(config) {
+        Ok(aliases) => aliases
+            .iter()
+            .filter(|alias| alias.s_ver() == PythonVersion::System.to_string())
+            .map(|alias| alias.name().to_string())
+            .collect(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(err) => return Err(err.into()),
+    };
+    let rows = interpreters
+        .iter()
+        .enumerate()
+        .map(|(index, interpreter)| {
+            let is_current = index == 0 && current == Some(&PythonVersion::System);
+            InstalledVersion {
+                version: PythonVersion::System,
+                path: interpreter.path.clone(),
+                current: is_current,
+                current_because: is_current
+                    .then(|| aliases.first().map(|name| format!("alias {}", name)))
+                    .flatten(),
+                aliases: aliases.clone(),
+                origin: Some(Origin::System),
+                installed_at: None,
+                size: 0,
+                python_version: Some(interpreter.version.clone()),
+            }
+        })
+        .collect();
+    Ok(rows)
+}
+
+/// The version file of the current directory when it selects `version`,
+/// otherwise an alias of it.
+fn current_because(
+    installed: &[PythonVersion],
+    version: &PythonVersion,
+    aliases: &[String],
+) -> Option<String> {
+    let version_file = std::env::current_dir()
+        .ok()
+        .and_then(|dir| find_up(dir, VERSION_FILE))
+        .filter(|path| {
+            get_user_version_for_file(path.clone())
+                .and_then(|input| input.to_version(installed).cloned())
+                .as_ref()
+                == Some(version)
+        });
+    match version_file {
+        Some(path) => Some(path.display().to_string()),
+        None => aliases.first().map(|name| format!("alias {}", name)),
+    }
+}
+
+fn modified_at(path: &Path) -> Option<String> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(chrono::DateTime::<chrono::Local>::from(modified).to_rfc3339())
+}
+
+fn print_table(rows: &[InstalledVersion]) {
+    let cells = rows
+        .iter()
+        .map(|row| {
+            let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+            if let Some(version) = &row.python_version {
+                // A system interpreter isn't ours to size, but where it lives matters
+                return [
+                    format!("{} ({})", row.version, version),
+                    or_dash((!row.aliases.is_empty()).then(|| row.aliases.join(", "))),
+                    or_dash(row.origin.map(|origin| origin.to_string())),
+                    "-".to_string(),
+                    row.path.display().to_string(),
+                ];
+            }
+            [
+                row.version.to_string(),
+                or_dash((!row.aliases.is_empty()).then(|| row.aliases.join(", "))),
+                or_dash(row.origin.map(|origin| origin.to_string())),
+                or_dash(row.installed_at.as_ref().map(|date| {
+                    // Only the day is interesting, e.g. `2024-05-01`
+                    date.split('T').next().unwrap_or(date).to_string()
+                })),
+                format_size(row.size),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let widths = (0..5)
+        .map(|column| cells.iter().map(|row| row[column].len()).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    for (row, cells) in rows.iter().zip(&cells) {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        if row.current {
+            let because = row
+                .current_because
+                .as_ref()
+                .map_or_else(String::new, |because| format!("  (set by {})", because));
+            println!("{} {}{}", "*".green(), line.green(), because);
+        } else {
+            println!("  {}", line.trim_end());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alias::create_alias;
+
+    #[test]
+    fn test_describe() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let v = |version: &str| PythonVersion::parse(version).unwrap();
+        let versions = vec![v("3.10.8"), v("3.11.6")];
+        for version in &versions {
+            let path = config.versions_dir().join(version.to_string());
+            std::fs::create_dir_all(&path).unwrap();
+            std::fs::write(path.join("python"), [0; 100]).unwrap();
+        }
+        InstallMetadata {
+            version: "3.11.6".to_string(),
+            installed_at: "2024-05-01T10:00:00+00:00".to_string(),
+            origin: Origin::Linked,
+            ..InstallMetadata::default()
+        }
+        .write(&config.versions_dir().join("3.11.6"))
+        .unwrap();
+        create_alias(&config, "default", &versions[1]).unwrap();
+
+        let rows = describe(&config, &versions, Some(&versions[1])).unwrap();
+        assert!(!rows[0].current);
+        assert_eq!(rows[0].origin, None);
+        assert!(rows[0].installed_at.is_some());
+        assert_eq!(rows[0].size, 100);
+        assert!(rows[1].current);
+        assert_eq!(rows[1].aliases, vec!["default"]);
+        assert_eq!(rows[1].origin, Some(Origin::Linked));
+
+        let json = serde_json::to_value(&rows[1]).unwrap();
+        assert_eq!(json["version"], "3.11.6");
+        assert_eq!(json["origin"], "linked");
+        assert_eq!(json["installed_at"], "2024-05-01T10:00:00+00:00");
+        assert!(json.get("python_version").is_none());
+    }
+
+    #[test]
+    fn test_describe_system() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let interpreter = |name: &str, version: &str| SystemInterpreter {
+            names: vec![name.to_string()],
+            path: PathBuf::from("/usr/bin").join(name),
+            version: PythonVersion::parse(version).unwrap(),
+        };
+        let interpreters = [
+            interpreter("python3", "3.11.2"),
+            interpreter("python3.9", "3.9.18"),
+        ];
+
+        let rows = describe_system(&config, &interpreters, Some(&PythonVersion::System)).unwrap();
+        assert!(rows[0].current);
+        assert!(!rows[1].current);
+        assert_eq!(rows[1].origin, Some(Origin::System));
+
+        let json = serde_json::to_value(&rows[0]).unwrap();
+        assert_eq!(json["version"], "system");
+        assert_eq!(json["python_version"], "3.11.2");
+        assert_eq!(json["path"], "/usr/bin/python3");
+    }
+}
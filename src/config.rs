@@ -36,6 +36,29 @@ pub struct MamimiConfig {
     )]
     pub mamimi_path: Option<PathBuf>,
 
+    /// Warn when a command resolves a Python whose series reached its end-of-life
+    #[clap(
+        long,
+        env = "MAMIMI_EOL_WARNINGS",
+        action = clap::ArgAction::Set,
+        default_value_t = true,
+        global = true,
+        hide_env_values = true
+    )]
+    pub eol_warnings: bool,
+
+    /// Seconds to wait for a server to accept a connection or send more data
+    #[clap(
+        long,
+        env = "MAMIMI_HTTP_TIMEOUT",
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        default_value_t = 30,
+        global = true,
+        hide_env_values = true
+    )]
+    pub http_timeout: u64,
+
     /// The log level of mamimi commands
     #[clap(
         long,
@@ -66,6 +89,8 @@ impl Default for MamimiConfig {
             python_ftp_mirror: Url::parse("https://www.python.org/ftp/python/").unwrap(),
             base_dir: None,
             mamimi_path: None,
+            eol_warnings: true,
+            http_timeout: 30,
             log_level: LogLevel::Info,
             version_file_strategy: VersionFileStrategy::default(),
         }
@@ -130,9 +155,21 @@ impl MamimiConfig {
             .ensure_exists_silently()
     }
 
+    pub fn cache_dir(&self) -> PathBuf {
+        self.base_dir_with_default()
+            .join("cache")
+            .ensure_exists_silently()
+    }
+
     #[cfg(test)]
     pub fn with_base_dir(mut self, base_dir: Option<PathBuf>) -> Self {
         self.base_dir = base_dir;
         self
     }
+
+    #[cfg(test)]
+    pub fn with_log_level(mut self, log_level: LogLevel) -> Self {
+        self.log_level = log_level;
+        self
+    }
 }
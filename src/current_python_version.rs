@@ -6,10 +6,8 @@ pub fn current_python_version(config: &MamimiConfig) -> Result<Option<PythonVers
     let multishell_path = config.multishell_path().ok_or(Error::EnvNotApplied)?;
 
     if let Ok(resolved_path) = std::fs::canonicalize(multishell_path) {
-        let installation_path = resolved_path
-            .parent()
-            .expect("multishell path can't be in the root");
-        let file_name = installation_path
+        // The multishell path links to the installation itself, e.g. `versions/3.11.6`
+        let file_name = resolved_path
             .file_name()
             .expect("Can't get file name")
             .to_str()
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+/// The total size of the files under `path`, without following symlinks.
+pub fn disk_usage(path: &Path) -> u64 {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return 0,
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| disk_usage(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// A size such as `41.2 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("lib/python3.11")).unwrap();
+        std::fs::write(dir.path().join("lib/python3.11/os.py"), [0; 1000]).unwrap();
+        std::fs::write(dir.path().join("README"), [0; 24]).unwrap();
+        assert_eq!(disk_usage(dir.path()), 1024);
+        assert_eq!(format_size(1024), "1.0 KiB");
+        assert_eq!(format_size(300), "300 B");
+        assert_eq!(format_size(45 * 1024 * 1024 + 512 * 1024), "45.5 MiB");
+    }
+}
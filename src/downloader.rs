@@ -0,0 +1,456 @@
+use crate::config::MamimiConfig;
+use crate::http::{self, HttpClient};
+use crate::log_level::LogLevel;
+use crate::path_ext::PathExt;
+use log::debug;
+use reqwest::header::RANGE;
+use reqwest::StatusCode;
+use std::fs::{File, OpenOptions};
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+use url::Url;
+
+const RETRIES: u32 = 5;
+const BACKOFF: Duration = Duration::from_millis(500);
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    HttpError(#[from] reqwest::Error),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    ClientError(#[from] http::Error),
+    #[error("{url} was not found")]
+    NotFound { url: Url },
+    #[error("Can't download {url}: the server responded with {status}")]
+    UnexpectedStatus { url: Url, status: StatusCode },
+    #[error("Can't download {url} after {attempts} attempts: {source}")]
+    RetriesExhausted {
+        url: Url,
+        attempts: u32,
+        source: Box<Error>,
+    },
+}
+
+/// Whether a failed attempt is worth repeating.
+enum Failure {
+    Transient(Error),
+    Permanent(Error),
+}
+
+impl From<reqwest::Error> for Failure {
+    fn from(err: reqwest::Error) -> Self {
+        if is_transient(&err) {
+            Self::Transient(err.into())
+        } else {
+            Self::Permanent(err.into())
+        }
+    }
+}
+
+/// Errors of the local `.part` file, such as a full disk, fail the same way
+/// on every attempt.
+impl From<std::io::Error> for Failure {
+    fn from(err: std::io::Error) -> Self {
+        Self::Permanent(err.into())
+    }
+}
+
+/// Timeouts, refused or dropped connections and bodies cut short. TLS and
+/// other setup errors won't go away by trying again.
+fn is_transient(err: &reqwest::Error) -> bool {
+    if err.is_timeout() || err.is_body() {
+        return true;
+    }
+    if !err.is_connect() && !err.is_request() {
+        return false;
+    }
+    let mut source = std::error::Error::source(err);
+    while let Some(inner) = source {
+        // The server closed the connection before it sent a response
+        if let Some(hyper) = inner.downcast_ref::<hyper::Error>() {
+            if hyper.is_incomplete_message() {
+                return true;
+            }
+        }
+        if let Some(io) = inner.downcast_ref::<std::io::Error>() {
+            // A failed TLS handshake is a connect error as well, but not a network one
+            return err.is_connect()
+                || matches!(
+                    io.kind(),
+                    std::io::ErrorKind::ConnectionReset
+                        | std::io::ErrorKind::ConnectionAborted
+                        | std::io::ErrorKind::UnexpectedEof
+                );
+        }
+        source = inner.source();
+    }
+    false
+}
+
+/// Where the archive at `url` is cached, keyed by its file name.
+pub fn cache_path(config: &MamimiConfig, url: &Url) -> PathBuf {
+    let file_name = url
+        .path_segments()
+        .and_then(|segments| segments.last())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("download");
+    config
+        .cache_dir()
+        .join("downloads")
+        .ensure_exists_silently()
+        .join(file_name)
+}
+
+pub struct Downloader {
+    client: HttpClient,
+    retries: u32,
+    backoff: Duration,
+    show_progress: bool,
+}
+
+impl Downloader {
+    pub fn new(config: &MamimiConfig) -> Result<Self, Error> {
+        Ok(Self {
+            client: HttpClient::new(config)?,
+            retries: RETRIES,
+            backoff: BACKOFF,
+            show_progress: std::io::stderr().is_terminal()
+                && config.log_level().is_writable(&LogLevel::Error),
+        })
+    }
+
+    /// Hides the progress bar unless `show`, e.g. while downloading several files at once.
+    pub fn show_progress(mut self, show: bool) -> Self {
+        self.show_progress &= show;
+        self
+    }
+
+    #[cfg(test)]
+    fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Downloads `url` to `destination` unless it is already there. The data is
+    /// written to `<destination>.part` first, and an interrupted download is
+    /// resumed from there with a `Range` request.
+    pub fn download(&self, url: &Url, destination: &Path) -> Result<(), Error> {
+        if destination.exists() {
+            debug!("Using the cached download at {:?}", destination);
+            return Ok(());
+        }
+        let mut part = destination.as_os_str().to_owned();
+        part.push(".part");
+        let part = PathBuf::from(part);
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match self.attempt(url, &part) {
+                Ok(()) => break,
+                Err(Failure::Permanent(err)) => return Err(err),
+                Err(Failure::Transient(err)) if attempt > self.retries => {
+                    return Err(Error::RetriesExhausted {
+                        url: http::redact(url),
+                        attempts: attempt,
+                        source: Box::new(err),
+                    })
+                }
+                Err(Failure::Transient(err)) => {
+                    let delay = self.backoff * 2u32.pow(attempt - 1);
+                    debug!(
+                        "Download attempt {} failed: {}. Retrying in {:?}",
+                        attempt, err, delay
+                    );
+                    std::thread::sleep(delay);
+                }
+            }
+        }
+        std::fs::rename(&part, destination)?;
+        Ok(())
+    }
+
+    fn attempt(&self, url: &Url, part: &Path) -> Result<(), Failure> {
+        let offset = std::fs::metadata(part).map(|x| x.len()).unwrap_or(0);
+        let mut request = self.client.get(url);
+        let url = &http::redact(url);
+        if offset > 0 {
+            debug!("Resuming the download of {} from byte {}", url, offset);
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        }
+        let mut response = request.send()?;
+        let status = response.status();
+        let (mut file, offset) = match status {
+            StatusCode::PARTIAL_CONTENT => (OpenOptions::new().append(true).open(part)?, offset),
+            StatusCode::OK => (File::create(part)?, 0),
+            StatusCode::RANGE_NOT_SATISFIABLE => {
+                std::fs::remove_file(part)?;
+                return Err(Failure::Transient(Error::UnexpectedStatus {
+                    url: url.clone(),
+                    status,
+                }));
+            }
+            StatusCode::NOT_FOUND => {
+                return Err(Failure::Permanent(Error::NotFound { url: url.clone() }))
+            }
+            status if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS => {
+                return Err(Failure::Transient(Error::UnexpectedStatus {
+                    url: url.clone(),
+                    status,
+                }))
+            }
+            status => {
+                return Err(Failure::Permanent(Error::UnexpectedStatus {
+                    url: url.clone(),
+                    status,
+                }))
+            }
+        };
+
+        let total = response.content_length().map(|length| length + offset);
+        let progress = ProgressBar {
+            enabled: self.show_progress,
+            total,
+        };
+        let mut written = offset;
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let read = response
+                .read(&mut buffer)
+                .map_err(|err| Failure::Transient(err.into()))?;
+            if read == 0 {
+                break;
+            }
+            file.write_all(&buffer[..read])?;
+            written += read as u64;
+            progress.update(written);
+        }
+        progress.finish();
+        file.flush()?;
+        if matches!(total, Some(total) if written < total) {
+            return Err(Failure::Transient(Error::IoError(
+                std::io::ErrorKind::UnexpectedEof.into(),
+            )));
+        }
+        Ok(())
+    }
+}
+
+struct ProgressBar {
+    enabled: bool,
+    total: Option<u64>,
+}
+
+impl ProgressBar {
+    const WIDTH: u64 = 30;
+
+    fn update(&self, written: u64) {
+        if !self.enabled {
+            return;
+        }
+        let mib = |bytes: u64| bytes as f64 / 1024.0 / 1024.0;
+        let line = match self.total {
+            Some(total) if total > 0 => {
+                let filled = (written * Self::WIDTH / total).min(Self::WIDTH);
+                format!(
+                    "[{}{}] {:>3}% {:.1}/{:.1} MiB",
+                    "#".repeat(filled as usize),
+                    "-".repeat((Self::WIDTH - filled) as usize),
+                    written * 100 / total,
+                    mib(written),
+                    mib(total)
+                )
+            }
+            _ => format!("{:.1} MiB", mib(written)),
+        };
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\r\x1b[2K    {}", line);
+        let _ = stderr.flush();
+    }
+
+    fn finish(&self) {
+        if self.enabled {
+            eprintln!();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    /// Serves `body`, cutting the connection halfway through the first response.
+    fn flaky_server(body: &'static [u8]) -> (Url, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/Python-3.9.6.tar.xz",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        let handle = std::thread::spawn(move || {
+            let mut ranges = vec![];
+            for (index, stream) in listener.incoming().take(2).enumerate() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut range = None;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("range: bytes=") {
+                        range = Some(value.trim().trim_end_matches('-').parse::<usize>().unwrap());
+                    }
+                }
+                ranges.push(format!("{:?}", range));
+                let start = range.unwrap_or(0);
+                let status = if range.is_some() {
+                    "206 Partial Content"
+                } else {
+                    "200 OK"
+                };
+                let rest = &body[start..];
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    rest.len()
+                )
+                .unwrap();
+                let sent = if index == 0 {
+                    rest.len() / 2
+                } else {
+                    rest.len()
+                };
+                stream.write_all(&rest[..sent]).unwrap();
+            }
+            ranges
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_resumes_dropped_connections() {
+        let body: &'static [u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+        let (url, server) = flaky_server(body);
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let destination = cache_path(&config, &url);
+        assert!(destination.ends_with("downloads/Python-3.9.6.tar.xz"));
+
+        Downloader::new(&config)
+            .unwrap()
+            .with_backoff(Duration::from_millis(1))
+            .download(&url, &destination)
+            .unwrap();
+
+        assert_eq!(std::fs::read(&destination).unwrap(), body);
+        assert_eq!(server.join().unwrap(), vec!["None", "Some(18)"]);
+    }
+
+    #[test]
+    fn test_not_found_is_not_retried() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/missing.tar.xz",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                stream,
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            )
+            .unwrap();
+        });
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let result = Downloader::new(&config)
+            .unwrap()
+            .download(&url, &cache_path(&config, &url));
+        assert!(matches!(result, Err(Error::NotFound { .. })));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_local_file_errors_are_not_retried() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/Python-3.9.6.tar.xz",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok"
+            )
+            .unwrap();
+        });
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let destination = base_dir.path().join("missing").join("Python-3.9.6.tar.xz");
+        let result = Downloader::new(&config)
+            .unwrap()
+            .download(&url, &destination);
+        assert!(matches!(result, Err(Error::IoError(_))));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_retries_connections_closed_before_the_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/Python-3.9.6.tar.xz",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        let server = std::thread::spawn(move || {
+            for (index, stream) in listener.incoming().take(2).enumerate() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                // The first connection is closed without a response
+                if index == 1 {
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok"
+                    )
+                    .unwrap();
+                }
+            }
+        });
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let destination = cache_path(&config, &url);
+        Downloader::new(&config)
+            .unwrap()
+            .with_backoff(Duration::from_millis(1))
+            .download(&url, &destination)
+            .unwrap();
+        assert_eq!(std::fs::read(&destination).unwrap(), b"ok");
+        server.join().unwrap();
+    }
+}
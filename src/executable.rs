@@ -0,0 +1,45 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+pub fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+pub fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// The file names `name` can have on disk, e.g. `python.exe` on Windows.
+fn candidate_names(name: &OsStr) -> Vec<PathBuf> {
+    if cfg!(windows) && Path::new(name).extension().is_none() {
+        vec![Path::new(name).with_extension("exe")]
+    } else {
+        vec![PathBuf::from(name)]
+    }
+}
+
+/// Every executable named `name` on `PATH`, in lookup order.
+pub fn find_all_in_path<S: AsRef<OsStr>>(name: S) -> Vec<PathBuf> {
+    let path = match std::env::var_os("PATH") {
+        Some(path) => path,
+        None => return vec![],
+    };
+    std::env::split_paths(&path)
+        .flat_map(|dir| {
+            candidate_names(name.as_ref())
+                .into_iter()
+                .map(move |file_name| dir.join(file_name))
+        })
+        .filter(|path| is_executable(path))
+        .collect()
+}
+
+/// The executable `PATH` resolves `name` to, like `which`.
+pub fn find_in_path<S: AsRef<OsStr>>(name: S) -> Option<PathBuf> {
+    find_all_in_path(name).into_iter().next()
+}
@@ -0,0 +1,245 @@
+use std::str::FromStr;
+use url::Url;
+
+/// The Python implementations besides CPython that mamimi can install.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Implementation {
+    PyPy,
+    GraalPy,
+    MicroPython,
+}
+
+impl Implementation {
+    pub const ALL: [Self; 3] = [Self::PyPy, Self::GraalPy, Self::MicroPython];
+
+    /// The prefix of its version names, as in `pypy3.10-7.3.15`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PyPy => "pypy",
+            Self::GraalPy => "graalpy",
+            Self::MicroPython => "micropython",
+        }
+    }
+
+    /// The interpreter an installation provides.
+    pub fn executable(&self) -> &'static str {
+        match self {
+            Self::PyPy => "pypy3",
+            Self::GraalPy => "graalpy",
+            Self::MicroPython => "micropython",
+        }
+    }
+
+    /// Whether its version names include the Python version it implements.
+    /// One PyPy release comes in a build for several of them.
+    pub fn names_language(&self) -> bool {
+        matches!(self, Self::PyPy)
+    }
+}
+
+impl std::fmt::Display for Implementation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Implementation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|implementation| implementation.name() == s)
+            .ok_or_else(|| format!("Unknown implementation: {}", s))
+    }
+}
+
+/// Splits a name such as `pypy3.10-7.3.15` into the implementation, the
+/// Python version it implements (possibly empty) and the release, if any.
+pub fn split(name: &str) -> Option<(Implementation, &str, Option<&str>)> {
+    let (implementation, rest) = Implementation::ALL.into_iter().find_map(|implementation| {
+        Some((implementation, name.strip_prefix(implementation.name())?))
+    })?;
+    Some(match rest.split_once('-') {
+        Some((language, release)) => (implementation, language, Some(release)),
+        None => (implementation, rest, None),
+    })
+}
+
+/// Parses the `3.10` of `pypy3.10`.
+pub fn parse_language(language: &str) -> Option<(u64, u64)> {
+    let (major, minor) = language.split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// A release of an alternative implementation, e.g. `pypy3.10-7.3.15` or
+/// `graalpy-24.0.1`.
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
+pub struct AlternativeVersion {
+    pub implementation: Implementation,
+    /// The Python version it implements, for implementations that name it.
+    pub language: Option<(u64, u64)>,
+    pub release: semver::Version,
+}
+
+impl AlternativeVersion {
+    /// `None` when `name` doesn't start with the name of an implementation.
+    pub fn parse(name: &str) -> Option<Result<Self, semver::Error>> {
+        let (implementation, language, release) = split(name)?;
+        // A name starting with letters never parses, which makes a fitting error
+        let invalid = || semver::Version::parse(name).unwrap_err();
+        let language = match language {
+            "" => None,
+            language => match parse_language(language) {
+                Some(language) => Some(language),
+                None => return Some(Err(invalid())),
+            },
+        };
+        if language.is_some() != implementation.names_language() {
+            return Some(Err(invalid()));
+        }
+        let release = match release {
+            Some(release) => semver::Version::parse(release),
+            None => Err(invalid()),
+        };
+        Some(release.map(|release| Self {
+            implementation,
+            language,
+            release,
+        }))
+    }
+
+    /// The archive with this release for the current platform, if there is one.
+    pub fn file_name(&self) -> Option<String> {
+        let release = &self.release;
+        match self.implementation {
+            Implementation::PyPy => {
+                let (major, minor) = self.language?;
+                Some(format!(
+                    "pypy{}.{}-v{}-{}.tar.bz2",
+                    major,
+                    minor,
+                    release,
+                    pypy_platform()?
+                ))
+            }
+            Implementation::GraalPy => {
+                let (os, arch) = graalpy_platform()?;
+                Some(format!(
+                    "graalpy-community-{}-{}-{}.tar.gz",
+                    release, os, arch
+                ))
+            }
+            // The unix port is built from source
+            Implementation::MicroPython if cfg!(unix) => {
+                Some(format!("micropython-{}.tar.xz", release))
+            }
+            Implementation::MicroPython => None,
+        }
+    }
+
+    pub fn package_url(&self) -> Option<Url> {
+        let file_name = self.file_name()?;
+        let url = match self.implementation {
+            Implementation::PyPy => format!("https://downloads.python.org/pypy/{}", file_name),
+            Implementation::GraalPy => format!(
+                "https://github.com/oracle/graalpython/releases/download/graal-{}/{}",
+                self.release, file_name
+            ),
+            Implementation::MicroPython => {
+                format!("https://micropython.org/resources/source/{}", file_name)
+            }
+        };
+        Some(Url::parse(&url).expect("Can't build the package url"))
+    }
+}
+
+impl std::fmt::Display for AlternativeVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.language {
+            Some((major, minor)) => write!(
+                f,
+                "{}{}.{}-{}",
+                self.implementation, major, minor, self.release
+            ),
+            None => write!(f, "{}-{}", self.implementation, self.release),
+        }
+    }
+}
+
+/// How PyPy names the current platform in its archives.
+fn pypy_platform() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("linux64"),
+        ("linux", "aarch64") => Some("aarch64"),
+        ("macos", "x86_64") => Some("macos_x86_64"),
+        ("macos", "aarch64") => Some("macos_arm64"),
+        _ => None,
+    }
+}
+
+/// How GraalPy names the current OS and architecture in its archives.
+fn graalpy_platform() -> Option<(&'static str, &'static str)> {
+    let os = match std::env::consts::OS {
+        os @ ("linux" | "macos") => os,
+        _ => return None,
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "aarch64",
+        _ => return None,
+    };
+    Some((os, arch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let parse = |name: &str| AlternativeVersion::parse(name).map(|v| v.map(|v| v.to_string()));
+        assert_eq!(
+            parse("pypy3.10-7.3.15").unwrap().unwrap(),
+            "pypy3.10-7.3.15"
+        );
+        assert_eq!(parse("graalpy-24.0.1").unwrap().unwrap(), "graalpy-24.0.1");
+        assert_eq!(
+            parse("micropython-1.22.2").unwrap().unwrap(),
+            "micropython-1.22.2"
+        );
+        assert!(parse("pypy-7.3.15").unwrap().is_err());
+        assert!(parse("graalpy3.10-24.0.1").unwrap().is_err());
+        assert!(parse("pypy3.10").unwrap().is_err());
+        assert!(parse("3.12.4").is_none());
+
+        let older = AlternativeVersion::parse("pypy3.9-7.3.16")
+            .unwrap()
+            .unwrap();
+        let newer = AlternativeVersion::parse("pypy3.10-7.3.15")
+            .unwrap()
+            .unwrap();
+        assert!(older < newer);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    fn test_package_url() {
+        let url = |name: &str| {
+            AlternativeVersion::parse(name)
+                .unwrap()
+                .unwrap()
+                .package_url()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            url("pypy3.10-7.3.15"),
+            "https://downloads.python.org/pypy/pypy3.10-v7.3.15-linux64.tar.bz2"
+        );
+        assert_eq!(
+            url("graalpy-24.0.1"),
+            "https://github.com/oracle/graalpython/releases/download/graal-24.0.1/graalpy-community-24.0.1-linux-amd64.tar.gz"
+        );
+    }
+}
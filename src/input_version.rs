@@ -1,3 +1,5 @@
+use crate::build_profile::BuildProfile;
+use crate::implementation::{self, Implementation};
 use crate::python_version::PythonVersion;
 use std::str::FromStr;
 
@@ -5,7 +7,22 @@ use std::str::FromStr;
 pub enum InputVersion {
     Major(u64),
     MajorMinor(u64, u64),
+    /// The newest patch of a series with a label, as in `3.12-debug` for a
+    /// build profile or `3.12-opt` for an interpreter linked under that name.
+    Labeled {
+        major: u64,
+        minor: u64,
+        label: String,
+    },
     Full(PythonVersion),
+    /// The newest release of an implementation, possibly narrowed down as in
+    /// `pypy3.10` or `graalpy-24.0`.
+    Alternative {
+        implementation: Implementation,
+        language: Option<(u64, u64)>,
+        /// The leading parts of the release.
+        release: Vec<u64>,
+    },
 }
 
 impl InputVersion {
@@ -19,10 +36,56 @@ impl InputVersion {
             .max()
     }
 
+    /// The profile a partial version asks for, as in `3.12-debug`.
+    pub fn profile(&self) -> Option<BuildProfile> {
+        match self {
+            Self::Labeled { label, .. } => label.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// The same version without a label, to match against releases which
+    /// never have one.
+    pub fn without_profile(&self) -> Self {
+        match self {
+            Self::Labeled { major, minor, .. } => Self::MajorMinor(*major, *minor),
+            other => other.clone(),
+        }
+    }
+
     pub fn matches(&self, version: &PythonVersion) -> bool {
         match (self, version) {
             (Self::Full(a), b) => a == b,
+            (
+                Self::Alternative {
+                    implementation,
+                    language,
+                    release,
+                },
+                PythonVersion::Alternative(other),
+            ) => {
+                let parts = [
+                    other.release.major,
+                    other.release.minor,
+                    other.release.patch,
+                ];
+                other.implementation == *implementation
+                    && other.release.pre.is_empty()
+                    && language.map_or(true, |language| other.language == Some(language))
+                    && release.iter().zip(parts).all(|(a, b)| *a == b)
+            }
+            (Self::Alternative { .. }, _) | (_, PythonVersion::Alternative(_)) => false,
             (_, PythonVersion::System) => false,
+            (
+                Self::Labeled {
+                    major,
+                    minor,
+                    label,
+                },
+                PythonVersion::Semver(other),
+            ) => *major == other.major && *minor == other.minor && other.pre.as_str() == label,
+            // A partial version means a plain build, never a `3.12.4-debug` one.
+            (_, PythonVersion::Semver(other)) if !other.pre.is_empty() => false,
             (Self::Major(major), PythonVersion::Semver(other)) => *major == other.major,
             (Self::MajorMinor(major, minor), PythonVersion::Semver(other)) => {
                 *major == other.major && *minor == other.minor
@@ -37,6 +100,27 @@ impl std::fmt::Display for InputVersion {
             Self::Full(x) => x.fmt(f),
             Self::Major(major) => write!(f, "{}.x.x", major),
             Self::MajorMinor(major, minor) => write!(f, "{}.{}.x", major, minor),
+            Self::Labeled {
+                major,
+                minor,
+                label,
+            } => write!(f, "{}.{}.x-{}", major, minor, label),
+            Self::Alternative {
+                implementation,
+                language,
+                release,
+            } => {
+                write!(f, "{}", implementation)?;
+                if let Some((major, minor)) = language {
+                    write!(f, "{}.{}", major, minor)?;
+                }
+                if !release.is_empty() {
+                    let mut parts = release.iter().map(ToString::to_string).collect::<Vec<_>>();
+                    parts.resize(3, "x".to_string());
+                    write!(f, "-{}", parts.join("."))?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -47,6 +131,14 @@ impl FromStr for InputVersion {
         match PythonVersion::parse(s) {
             Ok(v) => Ok(Self::Full(v)),
             Err(e) => {
+                if let Some((implementation, language, release)) =
+                    implementation::split(&s.trim().to_lowercase())
+                {
+                    return parse_alternative(implementation, language, release).ok_or(e);
+                }
+                if let Some((series, label)) = s.trim().split_once('-') {
+                    return parse_labeled(series, label).ok_or(e);
+                }
                 let mut parts = s.trim().split('.');
                 match (next_of::<u64, _>(&mut parts), next_of::<u64, _>(&mut parts)) {
                     (Some(major), None) => Ok(Self::Major(major)),
@@ -58,7 +150,132 @@ impl FromStr for InputVersion {
     }
 }
 
+/// A series with a label, as in `3.12-debug`. The label is kept as the
+/// pre-release of the versions it matches.
+fn parse_labeled(series: &str, label: &str) -> Option<InputVersion> {
+    let (major, minor) = series.split_once('.')?;
+    let label = semver::Prerelease::new(label)
+        .ok()
+        .filter(|label| !label.is_empty())?;
+    Some(InputVersion::Labeled {
+        major: major.parse().ok()?,
+        minor: minor.parse().ok()?,
+        label: label.to_string(),
+    })
+}
+
+/// The partial forms of an alternative version: `pypy`, `pypy3.10`,
+/// `pypy3.10-7.3` or `graalpy-24.0`.
+fn parse_alternative(
+    implementation: Implementation,
+    language: &str,
+    release: Option<&str>,
+) -> Option<InputVersion> {
+    let language = match language {
+        "" => None,
+        _ if !implementation.names_language() => return None,
+        language => Some(implementation::parse_language(language)?),
+    };
+    let release = match release {
+        Some(release) => release
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u64>>>()
+            .filter(|parts| parts.len() <= 3)?,
+        None => vec![],
+    };
+    Some(InputVersion::Alternative {
+        implementation,
+        language,
+        release,
+    })
+}
+
 fn next_of<'a, T: FromStr, It: Iterator<Item = &'a str>>(i: &mut It) -> Option<T> {
     let x = i.next()?;
     T::from_str(x).ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_versions_skip_profiles() {
+        let installed = ["3.12.4-debug", "3.12.3", "3.11.9"]
+            .iter()
+            .map(|v| PythonVersion::parse(v).unwrap())
+            .collect::<Vec<_>>();
+        let input = InputVersion::from_str("3.12").unwrap();
+        assert_eq!(input.to_version(&installed).unwrap().to_string(), "3.12.3");
+        let input = InputVersion::from_str("3.12.4-debug").unwrap();
+        assert_eq!(
+            input.to_version(&installed).unwrap().to_string(),
+            "3.12.4-debug"
+        );
+    }
+
+    #[test]
+    fn test_partial_versions_with_profiles() {
+        let installed = ["3.12.4-debug", "3.12.3", "3.11.9-debug"]
+            .iter()
+            .map(|v| PythonVersion::parse(v).unwrap())
+            .collect::<Vec<_>>();
+        let input = InputVersion::from_str("3.12-debug").unwrap();
+        assert_eq!(input.to_string(), "3.12.x-debug");
+        assert_eq!(input.profile(), Some(BuildProfile::Debug));
+        assert_eq!(
+            input.to_version(&installed).unwrap().to_string(),
+            "3.12.4-debug"
+        );
+        assert_eq!(
+            input
+                .without_profile()
+                .to_version(&installed)
+                .unwrap()
+                .to_string(),
+            "3.12.3"
+        );
+        assert!(InputVersion::from_str("3.11-optimized")
+            .unwrap()
+            .to_version(&installed)
+            .is_none());
+        let input = InputVersion::from_str("3.12-fast").unwrap();
+        assert_eq!(input.profile(), None);
+        assert!(input.to_version(&installed).is_none());
+        assert!(InputVersion::from_str("3-debug").is_err());
+        assert!(InputVersion::from_str("3.12-").is_err());
+    }
+
+    #[test]
+    fn test_alternative_implementations() {
+        let available = [
+            "3.10.14",
+            "pypy3.9-7.3.16",
+            "pypy3.10-7.3.15",
+            "pypy3.10-7.3.16",
+            "graalpy-23.1.2",
+            "graalpy-24.0.1",
+            "graalpy-24.1.0",
+        ]
+        .iter()
+        .map(|v| PythonVersion::parse(v).unwrap())
+        .collect::<Vec<_>>();
+        let pick = |input: &str| {
+            let input = InputVersion::from_str(input).unwrap();
+            input.to_version(&available).map(ToString::to_string)
+        };
+        assert_eq!(pick("pypy3.10").unwrap(), "pypy3.10-7.3.16");
+        assert_eq!(pick("pypy3.9").unwrap(), "pypy3.9-7.3.16");
+        assert_eq!(pick("pypy3.10-7.3.15").unwrap(), "pypy3.10-7.3.15");
+        assert_eq!(pick("pypy").unwrap(), "pypy3.10-7.3.16");
+        assert_eq!(pick("graalpy-24.0").unwrap(), "graalpy-24.0.1");
+        assert_eq!(pick("graalpy").unwrap(), "graalpy-24.1.0");
+        assert_eq!(pick("3.10").unwrap(), "3.10.14");
+        assert_eq!(pick("micropython"), None);
+
+        let input = InputVersion::from_str("graalpy-24.0").unwrap();
+        assert_eq!(input.to_string(), "graalpy-24.0.x");
+        assert!(InputVersion::from_str("graalpy3.10").is_err());
+    }
+}
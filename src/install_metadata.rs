@@ -0,0 +1,92 @@
+use crate::build_config::BuildSettings;
+use crate::build_profile::BuildProfile;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = ".mamimi-install.json";
+
+/// Where an installation came from.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Origin {
+    /// Built from a source tarball.
+    #[default]
+    Source,
+    /// Extracted from a prebuilt archive.
+    Prebuilt,
+    /// Taken over from another version manager.
+    Imported,
+    /// An interpreter elsewhere on disk, linked into the versions directory.
+    Linked,
+    /// An interpreter on `PATH` that mamimi doesn't manage.
+    System,
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Source => "source",
+            Self::Prebuilt => "prebuilt",
+            Self::Imported => "imported",
+            Self::Linked => "linked",
+            Self::System => "system",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What mamimi knows about how an installation was made, stored next to it.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct InstallMetadata {
+    pub version: String,
+    pub installed_at: String,
+    /// Metadata written before origins were recorded belongs to source builds.
+    #[serde(default)]
+    pub origin: Origin,
+    /// The prefix or interpreter a linked installation points to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linked_to: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<BuildProfile>,
+    #[serde(default)]
+    pub build: BuildSettings,
+}
+
+impl InstallMetadata {
+    pub fn path(installation_dir: &Path) -> PathBuf {
+        installation_dir.join(FILE_NAME)
+    }
+
+    pub fn read(installation_dir: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(Self::path(installation_dir)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn write(&self, installation_dir: &Path) -> std::io::Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        std::fs::write(Self::path(installation_dir), contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let metadata = InstallMetadata {
+            version: "3.9.6".to_string(),
+            installed_at: "2022-10-01T00:00:00+00:00".to_string(),
+            origin: Origin::Source,
+            linked_to: None,
+            profile: Some(BuildProfile::Debug),
+            build: BuildSettings {
+                cflags: Some("-O2".to_string()),
+                ..BuildSettings::default()
+            },
+        };
+        metadata.write(dir.path()).unwrap();
+        assert_eq!(InstallMetadata::read(dir.path()), Some(metadata));
+    }
+}
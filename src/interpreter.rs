@@ -0,0 +1,38 @@
+use crate::executable::is_executable;
+use crate::python_version::PythonVersion;
+use log::debug;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Prints the running version, also on interpreters too old for f-strings.
+const VERSION_SCRIPT: &str = "import sys; print('%d.%d.%d' % sys.version_info[:3])";
+
+/// Asks the interpreter at `python` for its version.
+pub fn probe_version(python: &Path) -> Option<PythonVersion> {
+    let output = Command::new(python)
+        .arg("-c")
+        .arg(VERSION_SCRIPT)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|err| debug!("Can't run {:?}: {}", python, err))
+        .ok()?;
+    if !output.status.success() {
+        debug!("{:?} exited with {}", python, output.status);
+        return None;
+    }
+    PythonVersion::parse(String::from_utf8_lossy(&output.stdout).trim()).ok()
+}
+
+/// The interpreter in the `bin` directory of an installation prefix such as
+/// `/opt/python3.12`, or in the directory itself as on Windows.
+pub fn find_in_prefix(prefix: &Path) -> Option<PathBuf> {
+    [prefix.join("bin"), prefix.to_path_buf()]
+        .iter()
+        .flat_map(|dir| {
+            ["python3", "python", "python.exe"]
+                .iter()
+                .map(move |name| dir.join(name))
+        })
+        .find(|path| is_executable(path))
+}
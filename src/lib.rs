@@ -8,19 +8,36 @@
 
 pub mod alias;
 pub mod archive;
+pub mod build_config;
+pub mod build_deps;
+pub mod build_log;
+pub mod build_profile;
 pub mod cli;
 pub mod commands;
 pub mod config;
 pub mod current_python_version;
+pub mod disk_usage;
+pub mod downloader;
+pub mod executable;
+pub mod http;
+pub mod implementation;
 pub mod input_version;
+pub mod install_metadata;
+pub mod interpreter;
+pub mod lock;
 pub mod log_level;
+pub mod openssl;
 pub mod path_ext;
+pub mod prompt;
 pub mod python_version;
+pub mod release_status;
 pub mod remote_python_index;
 pub mod shell;
+pub mod stdlib_modules;
 pub mod symlink;
 pub mod system_info;
 pub mod system_version;
+pub mod venv;
 pub mod version_file_strategy;
 pub mod version_files;
 
@@ -0,0 +1,148 @@
+use crate::config::MamimiConfig;
+use crate::log_level::LogLevel;
+use crate::path_ext::PathExt;
+use crate::python_version::PythonVersion;
+use colored::Colorize;
+use log::debug;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+pub fn locks_dir(config: &MamimiConfig) -> PathBuf {
+    config
+        .base_dir_with_default()
+        .join("locks")
+        .ensure_exists_silently()
+}
+
+/// An advisory lock on a file in `locks/`, shared by every mamimi process.
+/// The operating system releases it when the holder exits, so a crashed
+/// process never blocks the others; the PID it leaves behind is only used
+/// for messages.
+#[derive(Debug)]
+pub struct Lock {
+    name: String,
+    file: File,
+}
+
+impl Lock {
+    /// Guards the installation of `version` and everything that links to it.
+    pub fn version(config: &MamimiConfig, version: &PythonVersion) -> std::io::Result<Self> {
+        Self::acquire(config, &version.to_string())
+    }
+
+    /// Guards the aliases directory.
+    pub fn aliases(config: &MamimiConfig) -> std::io::Result<Self> {
+        Self::acquire(config, "aliases")
+    }
+
+    /// Guards one file of the download cache.
+    pub fn download(config: &MamimiConfig, file_name: &str) -> std::io::Result<Self> {
+        Self::acquire(config, &format!("download-{}", file_name))
+    }
+
+    /// Blocks until no other process holds the lock called `name`.
+    pub fn acquire(config: &MamimiConfig, name: &str) -> std::io::Result<Self> {
+        let path = locks_dir(config).join(format!("{}.lock", name));
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        match file.try_lock() {
+            Ok(()) => {
+                if let Some(pid) = read_pid(&mut file) {
+                    debug!("Taking over the stale lock on {} left by PID {}", name, pid);
+                }
+            }
+            Err(TryLockError::WouldBlock) => {
+                let holder = read_pid(&mut file).map_or_else(
+                    || "another process".to_string(),
+                    |pid| format!("PID {}", pid),
+                );
+                if config.log_level().is_writable(&LogLevel::Error) {
+                    eprintln!(
+                        "{} Waiting for the lock on {} held by {}",
+                        "==>".yellow(),
+                        name,
+                        holder
+                    );
+                }
+                file.lock()?;
+            }
+            Err(TryLockError::Error(err)) => return Err(err),
+        }
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        write!(file, "{}", std::process::id())?;
+        file.flush()?;
+        Ok(Self {
+            name: name.to_string(),
+            file,
+        })
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        // An empty file tells the next holder that the lock was released cleanly
+        if let Err(err) = self.file.set_len(0) {
+            debug!("Can't clear the lock on {}: {}", self.name, err);
+        }
+        let _ = self.file.unlock();
+    }
+}
+
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut contents = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_waits_for_the_holder() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let version = PythonVersion::parse("3.11.4").unwrap();
+        let lock = Lock::version(&config, &version).unwrap();
+        let path = locks_dir(&config).join("3.11.4.lock");
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            std::process::id().to_string()
+        );
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let _other = Lock::version(&config, &version).unwrap();
+                sender.send(()).unwrap();
+            });
+            assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+            drop(lock);
+            receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        });
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+    }
+
+    #[test]
+    fn test_takes_over_stale_locks() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let path = locks_dir(&config).join("aliases.lock");
+        // Left behind by a process that was killed while holding the lock
+        std::fs::write(&path, "999999").unwrap();
+        let _lock = Lock::aliases(&config).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            std::process::id().to_string()
+        );
+    }
+}
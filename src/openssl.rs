@@ -0,0 +1,191 @@
+use crate::executable::find_in_path;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Prefixes the compiler searches by default, which `configure` finds on its own.
+const SYSTEM_PREFIXES: &[&str] = &["/usr", "/usr/local"];
+
+/// Well-known prefixes, most specific first.
+const CANDIDATE_PREFIXES: &[&str] = &[
+    "/opt/homebrew/opt/openssl@3",
+    "/usr/local/opt/openssl@3",
+    "/opt/homebrew/opt/openssl@1.1",
+    "/usr/local/opt/openssl@1.1",
+    "/usr/local/ssl",
+    "/usr/local",
+    "/usr",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenSsl {
+    pub prefix: PathBuf,
+    /// The version from `opensslv.h`, e.g. `3.0.2` or `1.1.1w`.
+    pub version: String,
+}
+
+impl OpenSsl {
+    /// Reads the version of the OpenSSL installed at `prefix`.
+    pub fn at(prefix: impl Into<PathBuf>) -> Option<Self> {
+        let prefix = prefix.into();
+        let header = std::fs::read_to_string(prefix.join("include/openssl/opensslv.h")).ok()?;
+        let version = parse_version_header(&header)?;
+        Some(Self { prefix, version })
+    }
+
+    /// CPython 3.10+ needs OpenSSL 1.1.1 or newer.
+    pub fn is_supported(&self) -> bool {
+        let mut parts = self.version.split('.');
+        let major = parts.next().and_then(|x| x.parse::<u32>().ok());
+        let minor = parts.next().and_then(|x| x.parse::<u32>().ok());
+        let patch = parts.next().unwrap_or_default();
+        match (major, minor) {
+            (Some(major), _) if major >= 3 => true,
+            (Some(1), Some(1)) => patch.starts_with('1'),
+            _ => false,
+        }
+    }
+
+    pub fn is_system(&self) -> bool {
+        SYSTEM_PREFIXES
+            .iter()
+            .any(|prefix| self.prefix == Path::new(prefix))
+    }
+}
+
+/// Extracts the version from `OPENSSL_VERSION_STR` (3.x) or `OPENSSL_VERSION_TEXT` (1.x).
+fn parse_version_header(header: &str) -> Option<String> {
+    let define = |name: &str| {
+        header.lines().find_map(|line| {
+            let line = line.trim_start_matches(|c: char| c == '#' || c.is_whitespace());
+            let value = line
+                .strip_prefix("define")?
+                .trim_start()
+                .strip_prefix(name)?;
+            let value = value.trim();
+            value
+                .starts_with('"')
+                .then(|| value.trim_matches('"').to_string())
+        })
+    };
+    if let Some(version) = define("OPENSSL_VERSION_STR") {
+        return Some(version);
+    }
+    let text = define("OPENSSL_VERSION_TEXT")?;
+    let mut words = text.split_whitespace();
+    match words.next()? {
+        "OpenSSL" => words.next().map(ToString::to_string),
+        _ => None,
+    }
+}
+
+fn pkg_config_prefix() -> Option<PathBuf> {
+    let output = Command::new("pkg-config")
+        .args(["--variable=prefix", "openssl"])
+        .output()
+        .ok()?;
+    let prefix = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !prefix.is_empty()).then(|| PathBuf::from(prefix))
+}
+
+fn brew_prefixes() -> Vec<PathBuf> {
+    if find_in_path("brew").is_none() {
+        return vec![];
+    }
+    ["openssl@3", "openssl@1.1"]
+        .iter()
+        .filter_map(|formula| {
+            let output = Command::new("brew")
+                .args(["--prefix", formula])
+                .output()
+                .ok()?;
+            let prefix = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            (output.status.success() && !prefix.is_empty()).then(|| PathBuf::from(prefix))
+        })
+        .collect()
+}
+
+/// Finds a supported OpenSSL, checking `OPENSSL_DIR`, pkg-config, Homebrew and
+/// the usual prefixes in that order.
+pub fn detect() -> Option<OpenSsl> {
+    let mut prefixes = vec![];
+    prefixes.extend(std::env::var_os("OPENSSL_DIR").map(PathBuf::from));
+    prefixes.extend(pkg_config_prefix());
+    if cfg!(target_os = "macos") {
+        prefixes.extend(brew_prefixes());
+    }
+    prefixes.extend(CANDIDATE_PREFIXES.iter().map(PathBuf::from));
+    find_supported(prefixes)
+}
+
+fn find_supported(prefixes: impl IntoIterator<Item = PathBuf>) -> Option<OpenSsl> {
+    prefixes.into_iter().find_map(|prefix| {
+        let openssl = OpenSsl::at(&prefix)?;
+        if openssl.is_supported() {
+            Some(openssl)
+        } else {
+            log::debug!(
+                "Skipping OpenSSL {} at {:?}: 1.1.1 or newer is required",
+                openssl.version,
+                prefix
+            );
+            None
+        }
+    })
+}
+
+/// The value for `--with-openssl-dir`, only when `configure` wouldn't find OpenSSL by itself.
+pub fn configure_dir() -> Option<PathBuf> {
+    detect()
+        .filter(|openssl| !openssl.is_system())
+        .map(|openssl| openssl.prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_openssl(header: &str) -> tempfile::TempDir {
+        let prefix = tempfile::tempdir().unwrap();
+        let include = prefix.path().join("include").join("openssl");
+        std::fs::create_dir_all(&include).unwrap();
+        std::fs::write(include.join("opensslv.h"), header).unwrap();
+        prefix
+    }
+
+    #[test]
+    fn test_parse_version_header() {
+        let v3 = "# define OPENSSL_VERSION_STR \"3.0.2\"\n# define OPENSSL_VERSION_TEXT \"OpenSSL 3.0.2 15 Mar 2022\"\n";
+        assert_eq!(parse_version_header(v3).as_deref(), Some("3.0.2"));
+        let v1 = "# define OPENSSL_VERSION_TEXT  \"OpenSSL 1.1.1w  11 Sep 2023\"\n";
+        assert_eq!(parse_version_header(v1).as_deref(), Some("1.1.1w"));
+        let libressl = "#define OPENSSL_VERSION_TEXT \"LibreSSL 3.3.6\"\n";
+        assert_eq!(parse_version_header(libressl), None);
+    }
+
+    #[test]
+    fn test_is_supported() {
+        let openssl = |version: &str| OpenSsl {
+            prefix: PathBuf::from("/opt/openssl"),
+            version: version.to_string(),
+        };
+        assert!(openssl("3.2.1").is_supported());
+        assert!(openssl("1.1.1w").is_supported());
+        assert!(!openssl("1.1.0l").is_supported());
+        assert!(!openssl("1.0.2u").is_supported());
+        assert!(!openssl("3.0.2").is_system());
+    }
+
+    #[test]
+    fn test_find_supported_skips_old_versions() {
+        let old = fake_openssl("# define OPENSSL_VERSION_TEXT \"OpenSSL 1.0.2u  20 Dec 2019\"\n");
+        let new = fake_openssl("# define OPENSSL_VERSION_STR \"3.1.4\"\n");
+        let found = find_supported(vec![
+            PathBuf::from("/does/not/exist"),
+            old.path().to_path_buf(),
+            new.path().to_path_buf(),
+        ])
+        .unwrap();
+        assert_eq!(found.prefix, new.path());
+        assert_eq!(found.version, "3.1.4");
+    }
+}
@@ -0,0 +1,14 @@
+use std::io::{IsTerminal, Write};
+
+/// Asks a yes/no question on the terminal. Without a terminal to answer on,
+/// the answer is no.
+pub fn confirm(question: &str) -> std::io::Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    eprint!("{} [y/N] ", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -1,4 +1,6 @@
+use crate::build_profile::BuildProfile;
 use crate::config;
+use crate::implementation::AlternativeVersion;
 use crate::system_version;
 use std::str::FromStr;
 
@@ -6,6 +8,8 @@ use std::str::FromStr;
 pub enum PythonVersion {
     Semver(semver::Version),
     System,
+    /// A release of PyPy, GraalPy or MicroPython.
+    Alternative(AlternativeVersion),
 }
 
 pub fn is_dotfile(dir: &std::fs::DirEntry) -> bool {
@@ -15,6 +19,31 @@ pub fn is_dotfile(dir: &std::fs::DirEntry) -> bool {
         .unwrap_or(false)
 }
 
+/// Every version in the versions directory, oldest first.
+pub fn installed_versions(config: &config::MamimiConfig) -> std::io::Result<Vec<PythonVersion>> {
+    let entries = match std::fs::read_dir(config.versions_dir()) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    let mut versions = vec![];
+    for entry in entries {
+        let entry = entry?;
+        if is_dotfile(&entry) {
+            continue;
+        }
+        if let Some(version) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| PythonVersion::parse(name).ok())
+        {
+            versions.push(version);
+        }
+    }
+    versions.sort();
+    Ok(versions)
+}
+
 fn first_letter_is_number(s: &str) -> bool {
     s.chars().next().map_or(false, |x| x.is_digit(10))
 }
@@ -24,8 +53,10 @@ impl PythonVersion {
         let lowercased = version_str.as_ref().to_lowercase();
         if lowercased == system_version::display_name() {
             Ok(Self::System)
+        } else if let Some(version) = AlternativeVersion::parse(&lowercased) {
+            Ok(Self::Alternative(version?))
         } else {
-            unreachable!()
+            Ok(Self::Semver(semver::Version::parse(&lowercased)?))
         }
     }
 
@@ -34,21 +65,43 @@ impl PythonVersion {
         config: &crate::config::MamimiConfig,
     ) -> Option<std::path::PathBuf> {
         match self {
-            v @ Self::Semver(_) => Some(
-                config
-                    .installations_dir()
-                    .join(v.to_string())
-                    .join("installation"),
-            ),
-            Self::System => None,
+            v @ (Self::Semver(_) | Self::Alternative(_)) => {
+                Some(config.versions_dir().join(v.to_string()))
+            }
+            Self::System => Some(system_version::path(config)),
         }
     }
 
     pub fn root_path(&self, config: &config::MamimiConfig) -> Option<std::path::PathBuf> {
-        let path = self.installation_path(config).unwrap();
-        let mut canon_path = path.canonicalize().ok()?;
-        canon_path.pop();
-        Some(canon_path)
+        let path = self.installation_path(config)?;
+        path.canonicalize().ok()
+    }
+
+    /// The build profile encoded as the suffix of the version, as in `3.12.4-debug`.
+    pub fn profile(&self) -> Option<BuildProfile> {
+        match self {
+            Self::Semver(v) if !v.pre.is_empty() => v.pre.as_str().parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn with_profile(&self, profile: Option<BuildProfile>) -> Self {
+        match self {
+            Self::Semver(v) => {
+                let mut v = v.clone();
+                v.pre = match profile {
+                    Some(profile) => semver::Prerelease::new(profile.name())
+                        .expect("profile names are valid identifiers"),
+                    None => semver::Prerelease::EMPTY,
+                };
+                Self::Semver(v)
+            }
+            other => other.clone(),
+        }
+    }
+
+    pub fn without_profile(&self) -> Self {
+        self.with_profile(None)
     }
 }
 
@@ -62,11 +115,21 @@ impl<'de> serde::Deserialize<'de> for PythonVersion {
     }
 }
 
+impl serde::Serialize for PythonVersion {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 impl std::fmt::Display for PythonVersion {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Semver(semver) => write!(f, "{}", semver),
             Self::System => write!(f, "system"),
+            Self::Alternative(version) => version.fmt(f),
         }
     }
 }
@@ -82,7 +145,27 @@ impl PartialEq<semver::Version> for PythonVersion {
     fn eq(&self, other: &semver::Version) -> bool {
         match self {
             Self::Semver(v) => v == other,
-            Self::System => false,
+            Self::System | Self::Alternative(_) => false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_suffix() {
+        let version = PythonVersion::parse("3.12.4-debug").unwrap();
+        assert_eq!(version.profile(), Some(BuildProfile::Debug));
+        assert_eq!(version.without_profile().to_string(), "3.12.4");
+        let free_threaded = PythonVersion::parse("3.13.0")
+            .unwrap()
+            .with_profile(Some(BuildProfile::FreeThreaded));
+        assert_eq!(free_threaded.to_string(), "3.13.0-free-threaded");
+        assert_eq!(
+            PythonVersion::parse("3.13.0-free-threaded").unwrap(),
+            free_threaded
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use crate::config::MamimiConfig;
+use crate::http::HttpClient;
+use crate::log_level::LogLevel;
+use crate::outln;
+use crate::python_version::PythonVersion;
+use chrono::NaiveDate;
+use colored::Colorize;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use thiserror::Error;
+use url::Url;
+
+/// The support schedule shipped with mamimi. Newer data can be fetched with
+/// [`ReleaseTable::refresh`], which caches it next to the other downloads.
+const EMBEDDED: &str = include_str!("release_status.json");
+
+/// Serves the same format as the embedded table.
+pub const RELEASE_STATUS_URL: &str = "https://endoflife.date/api/python.json";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    HttpError(#[from] crate::http::Error),
+    #[error(transparent)]
+    RequestError(#[from] reqwest::Error),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid release status data: {0}")]
+    ParseError(#[from] serde_json::Error),
+}
+
+/// A date, or a bare flag for series whose date isn't known.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+enum Milestone {
+    Date(NaiveDate),
+    Flag(bool),
+}
+
+impl Milestone {
+    fn reached(&self, today: NaiveDate) -> bool {
+        match self {
+            Self::Date(date) => *date <= today,
+            Self::Flag(reached) => *reached,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Series {
+    cycle: String,
+    /// When bugfix releases end and only security fixes follow.
+    support: Option<Milestone>,
+    eol: Option<Milestone>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SupportStatus {
+    Bugfix,
+    Security,
+    EndOfLife,
+    Unknown,
+}
+
+impl std::fmt::Display for SupportStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Bugfix => "bugfix",
+            Self::Security => "security",
+            Self::EndOfLife => "end-of-life",
+            Self::Unknown => "unknown",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReleaseTable {
+    series: Vec<Series>,
+}
+
+pub fn cache_path(config: &MamimiConfig) -> PathBuf {
+    config.cache_dir().join("release-status.json")
+}
+
+impl ReleaseTable {
+    pub fn embedded() -> Self {
+        Self::parse(EMBEDDED).expect("the embedded release status table is valid")
+    }
+
+    /// The cached table from the last [`refresh`](Self::refresh), or the embedded one.
+    pub fn load(config: &MamimiConfig) -> Self {
+        let path = cache_path(config);
+        match std::fs::read_to_string(&path).map(|contents| Self::parse(&contents)) {
+            Ok(Ok(table)) => table,
+            Ok(Err(err)) => {
+                debug!("Ignoring the cached release status at {:?}: {}", path, err);
+                Self::embedded()
+            }
+            Err(_) => Self::embedded(),
+        }
+    }
+
+    /// Downloads the current schedule and caches it for [`load`](Self::load).
+    pub fn refresh(config: &MamimiConfig) -> Result<Self, Error> {
+        let url = Url::parse(RELEASE_STATUS_URL).expect("the release status URL is valid");
+        let contents = HttpClient::new(config)?
+            .get(&url)
+            .send()?
+            .error_for_status()?
+            .text()?;
+        let table = Self::parse(&contents)?;
+        std::fs::write(cache_path(config), contents)?;
+        Ok(table)
+    }
+
+    fn parse(contents: &str) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            series: serde_json::from_str(contents)?,
+        })
+    }
+
+    fn series(&self, version: &PythonVersion) -> Option<&Series> {
+        let cycle = match version {
+            PythonVersion::Semver(v) => format!("{}.{}", v.major, v.minor),
+            PythonVersion::System | PythonVersion::Alternative(_) => return None,
+        };
+        self.series.iter().find(|series| series.cycle == cycle)
+    }
+
+    pub fn status(&self, version: &PythonVersion, today: NaiveDate) -> SupportStatus {
+        let series = match self.series(version) {
+            Some(series) => series,
+            None => return SupportStatus::Unknown,
+        };
+        let reached = |milestone: Option<Milestone>| milestone.map_or(false, |m| m.reached(today));
+        if reached(series.eol) {
+            SupportStatus::EndOfLife
+        } else if reached(series.support) {
+            SupportStatus::Security
+        } else {
+            SupportStatus::Bugfix
+        }
+    }
+
+    /// The day the series of `version` stops receiving any fixes, if known.
+    pub fn eol_date(&self, version: &PythonVersion) -> Option<NaiveDate> {
+        match self.series(version)?.eol? {
+            Milestone::Date(date) => Some(date),
+            Milestone::Flag(_) => None,
+        }
+    }
+}
+
+pub fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// Prints a one-line warning when the series of `version` is past its
+/// end-of-life, unless `eol_warnings` is off or the log level is quiet.
+pub fn warn_if_eol(config: &MamimiConfig, version: &PythonVersion) {
+    if let Some(warning) = eol_warning(config, version, today()) {
+        outln!(config, Error, "{} {}", "warning:".yellow().bold(), warning);
+    }
+}
+
+fn eol_warning(config: &MamimiConfig, version: &PythonVersion, today: NaiveDate) -> Option<String> {
+    let v = match version {
+        PythonVersion::Semver(v) if config.eol_warnings => v,
+        _ => return None,
+    };
+    if !config.log_level().is_writable(&LogLevel::Error) {
+        return None;
+    }
+    let table = ReleaseTable::load(config);
+    if table.status(version, today) != SupportStatus::EndOfLife {
+        return None;
+    }
+    let since = table
+        .eol_date(version)
+        .map_or_else(String::new, |date| format!(" on {}", date));
+    Some(format!(
+        "Python {}.{} reached its end-of-life{} and no longer gets security fixes",
+        v.major, v.minor, since
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        let table = ReleaseTable::embedded();
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let status = |version: &str| table.status(&PythonVersion::parse(version).unwrap(), today);
+        assert_eq!(status("3.13.1"), SupportStatus::Bugfix);
+        assert_eq!(status("3.11.9"), SupportStatus::Security);
+        assert_eq!(status("3.7.17"), SupportStatus::EndOfLife);
+        assert_eq!(status("3.99.0"), SupportStatus::Unknown);
+        assert_eq!(
+            table.eol_date(&PythonVersion::parse("3.7.17").unwrap()),
+            NaiveDate::from_ymd_opt(2023, 6, 27)
+        );
+    }
+
+    #[test]
+    fn test_load_prefers_a_valid_cache() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let version = PythonVersion::parse("3.13.0").unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+
+        std::fs::write(cache_path(&config), "not json").unwrap();
+        assert_eq!(
+            ReleaseTable::load(&config).status(&version, today),
+            SupportStatus::Bugfix
+        );
+
+        std::fs::write(
+            cache_path(&config),
+            r#"[{"cycle": "3.13", "support": true, "eol": false, "latest": "3.13.1"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            ReleaseTable::load(&config).status(&version, today),
+            SupportStatus::Security
+        );
+    }
+
+    #[test]
+    fn test_eol_warning() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let warning = |config: &MamimiConfig, version: &str| {
+            eol_warning(config, &PythonVersion::parse(version).unwrap(), today)
+        };
+
+        assert_eq!(
+            warning(&config, "3.7.17").as_deref(),
+            Some("Python 3.7 reached its end-of-life on 2023-06-27 and no longer gets security fixes")
+        );
+        assert_eq!(warning(&config, "3.12.4"), None);
+
+        let mut disabled =
+            MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        disabled.eol_warnings = false;
+        assert_eq!(warning(&disabled, "3.7.17"), None);
+
+        let quiet = MamimiConfig::default()
+            .with_base_dir(Some(base_dir.path().to_path_buf()))
+            .with_log_level(LogLevel::Quiet);
+        assert_eq!(warning(&quiet, "3.7.17"), None);
+    }
+}
@@ -1,6 +1,18 @@
+use crate::config::MamimiConfig;
+use crate::http::HttpClient;
+use crate::implementation::{AlternativeVersion, Implementation};
 use crate::python_version::PythonVersion;
+use log::debug;
 use scraper;
 use serde::Deserialize;
+use url::Url;
+
+/// Every PyPy release with the files built for it.
+const PYPY_VERSIONS_URL: &str = "https://downloads.python.org/pypy/versions.json";
+const GRAALPY_RELEASES_URL: &str =
+    "https://api.github.com/repos/oracle/graalpython/releases?per_page=100";
+const MICROPYTHON_RELEASES_URL: &str =
+    "https://api.github.com/repos/micropython/micropython/releases?per_page=100";
 
 #[derive(Deserialize, Debug)]
 pub struct IndexedPythonVersion {
@@ -9,11 +21,13 @@ pub struct IndexedPythonVersion {
     pub url: String,
 }
 
-pub fn list() -> Result<Vec<IndexedPythonVersion>, reqwest::Error> {
-    let value = reqwest::blocking::get(format!("https://www.python.org/ftp/python/").as_str())
-        .unwrap()
-        .text()
-        .unwrap();
+pub fn list(config: &MamimiConfig) -> Result<Vec<IndexedPythonVersion>, crate::http::Error> {
+    let mirror = &config.python_ftp_mirror;
+    let value = HttpClient::new(config)?
+        .get(mirror)
+        .send()?
+        .error_for_status()?
+        .text()?;
     let doc = scraper::Html::parse_document(&value);
     let sel = scraper::Selector::parse("a").unwrap();
 
@@ -27,11 +41,215 @@ pub fn list() -> Result<Vec<IndexedPythonVersion>, reqwest::Error> {
         match PythonVersion::parse(&version.to_string()) {
             Ok(v) => versions.push(IndexedPythonVersion {
                 python_version: v.clone(),
-                url: format!("https://www.python.org/ftp/python/{}", v.to_string()),
+                url: format!("{}{}", crate::http::redact(mirror), v),
             }),
             Err(_) => continue,
         }
     }
     versions.sort_by(|a, b| a.python_version.cmp(&b.python_version));
+    if let Err(err) = write_cache(config, &versions) {
+        debug!("Can't cache the remote versions: {}", err);
+    }
+    Ok(versions)
+}
+
+#[derive(Deserialize, Debug)]
+struct PypyRelease {
+    pypy_version: String,
+    python_version: String,
+    stable: bool,
+    files: Vec<PypyFile>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PypyFile {
+    filename: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GithubRelease {
+    tag_name: String,
+    prerelease: bool,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GithubAsset {
+    name: String,
+}
+
+/// The releases of `implementation` with a build for the current platform.
+pub fn list_alternative(
+    config: &MamimiConfig,
+    implementation: Implementation,
+) -> Result<Vec<IndexedPythonVersion>, crate::http::Error> {
+    let client = HttpClient::new(config)?;
+    let get = |url: &str| {
+        let url = Url::parse(url).expect("the index url is valid");
+        client.get(&url).send()?.error_for_status()
+    };
+    let releases = match implementation {
+        Implementation::PyPy => pypy_releases(get(PYPY_VERSIONS_URL)?.json()?),
+        Implementation::GraalPy => {
+            github_releases(implementation, "graal-", get(GRAALPY_RELEASES_URL)?.json()?)
+        }
+        Implementation::MicroPython => {
+            github_releases(implementation, "v", get(MICROPYTHON_RELEASES_URL)?.json()?)
+        }
+    };
+    let mut versions = releases
+        .into_iter()
+        .filter_map(|version| {
+            Some(IndexedPythonVersion {
+                url: version.package_url()?.to_string(),
+                python_version: PythonVersion::Alternative(version),
+            })
+        })
+        .collect::<Vec<_>>();
+    versions.sort_by(|a, b| a.python_version.cmp(&b.python_version));
     Ok(versions)
 }
+
+fn pypy_releases(releases: Vec<PypyRelease>) -> Vec<AlternativeVersion> {
+    releases
+        .into_iter()
+        .filter(|release| release.stable)
+        .filter_map(|release| {
+            let language = release
+                .python_version
+                .splitn(3, '.')
+                .take(2)
+                .collect::<Vec<_>>();
+            let version = AlternativeVersion {
+                implementation: Implementation::PyPy,
+                language: crate::implementation::parse_language(&language.join(".")),
+                release: semver::Version::parse(&release.pypy_version).ok()?,
+            };
+            let file_name = version.file_name()?;
+            release
+                .files
+                .iter()
+                .any(|file| file.filename == file_name)
+                .then_some(version)
+        })
+        .collect()
+}
+
+/// Releases tagged `<tag_prefix><version>` that have an archive for the
+/// current platform. MicroPython publishes its sources elsewhere.
+fn github_releases(
+    implementation: Implementation,
+    tag_prefix: &str,
+    releases: Vec<GithubRelease>,
+) -> Vec<AlternativeVersion> {
+    releases
+        .into_iter()
+        .filter(|release| !release.prerelease)
+        .filter_map(|release| {
+            let tag = release.tag_name.strip_prefix(tag_prefix)?;
+            let version = AlternativeVersion {
+                implementation,
+                language: None,
+                release: semver::Version::parse(tag).ok()?,
+            };
+            let file_name = version.file_name()?;
+            let has_archive = implementation == Implementation::MicroPython
+                || release.assets.iter().any(|asset| asset.name == file_name);
+            has_archive.then_some(version)
+        })
+        .collect()
+}
+
+fn cache_path(config: &MamimiConfig) -> std::path::PathBuf {
+    config.cache_dir().join("remote-versions")
+}
+
+/// Stores the listed versions so that shell completions can suggest them
+/// without a network round trip.
+fn write_cache(config: &MamimiConfig, versions: &[IndexedPythonVersion]) -> std::io::Result<()> {
+    let contents = versions
+        .iter()
+        .map(|x| format!("{}\n", x.python_version))
+        .collect::<String>();
+    std::fs::write(cache_path(config), contents)
+}
+
+/// Reads the versions stored by the last successful [`list`], if any.
+pub fn read_cache(config: &MamimiConfig) -> Vec<PythonVersion> {
+    std::fs::read_to_string(cache_path(config))
+        .map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| PythonVersion::parse(line.trim()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config = MamimiConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+        assert!(read_cache(&config).is_empty());
+        let versions = ["3.11.9", "3.12.4"]
+            .iter()
+            .map(|version| IndexedPythonVersion {
+                python_version: PythonVersion::parse(version).unwrap(),
+                url: String::new(),
+            })
+            .collect::<Vec<_>>();
+        write_cache(&config, &versions).unwrap();
+        let cached = read_cache(&config)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(cached, vec!["3.11.9", "3.12.4"]);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    fn test_pypy_releases() {
+        let releases = serde_json::from_str(
+            r#"[
+                {"pypy_version": "7.3.16", "python_version": "3.10.14", "stable": true,
+                 "files": [{"filename": "pypy3.10-v7.3.16-linux64.tar.bz2"}]},
+                {"pypy_version": "7.3.16", "python_version": "3.9.19", "stable": true,
+                 "files": [{"filename": "pypy3.9-v7.3.16-aarch64.tar.bz2"}]},
+                {"pypy_version": "nightly", "python_version": "3.10", "stable": false,
+                 "files": [{"filename": "pypy-c-jit-latest-linux64.tar.bz2"}]}
+            ]"#,
+        )
+        .unwrap();
+        let versions = pypy_releases(releases)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec!["pypy3.10-7.3.16"]);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    fn test_github_releases() {
+        let releases = serde_json::from_str(
+            r#"[
+                {"tag_name": "graal-24.0.1", "prerelease": false,
+                 "assets": [{"name": "graalpy-community-24.0.1-linux-amd64.tar.gz"}]},
+                {"tag_name": "graal-24.1.0", "prerelease": true,
+                 "assets": [{"name": "graalpy-community-24.1.0-linux-amd64.tar.gz"}]},
+                {"tag_name": "graal-23.0.0", "prerelease": false,
+                 "assets": [{"name": "graalpy-23.0.0-windows-amd64.zip"}]}
+            ]"#,
+        )
+        .unwrap();
+        let versions = github_releases(Implementation::GraalPy, "graal-", releases)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec!["graalpy-24.0.1"]);
+    }
+}
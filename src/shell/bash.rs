@@ -1,3 +1,4 @@
+use crate::shell::hook;
 use crate::shell::Shell;
 use anyhow::Ok;
 use indoc::indoc;
@@ -16,19 +17,22 @@ impl Shell for Bash {
     fn rehash(&self) -> Option<String> {
         Some("rehash".to_string())
     }
-    fn to_clap_shell(&self) -> clap_complete::Shell {
-        clap_complete::Shell::Bash
+    fn to_clap_shell(&self) -> anyhow::Result<clap_complete::Shell> {
+        Ok(clap_complete::Shell::Bash)
     }
-    fn use_on_cd(&self, config: &crate::config::MamimiConfig) -> String {
-        indoc!(
+    fn use_on_cd(&self, _config: &crate::config::MamimiConfig) -> String {
+        hook::render(indoc!(
             r#"
+                {hook}() {
+                    {command}
+                }
                 __mamimicd() {
                     \cd "$@" || return $?
-                    mamimi --log-level quiet local
+                    {hook}
                 }
                 alias cd=__mamimicd
+                {hook}
             "#
-        )
-        .into()
+        ))
     }
 }
@@ -0,0 +1,54 @@
+use crate::shell::hook;
+use crate::shell::Shell;
+use anyhow::Ok;
+use indoc::indoc;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct Elvish;
+
+impl Shell for Elvish {
+    fn path(&self, path: &Path) -> anyhow::Result<String> {
+        Ok(format!(
+            "set paths = [{:?} $@paths]",
+            path.to_str().unwrap()
+        ))
+    }
+    fn set_env_var(&self, name: &str, value: &str) -> String {
+        format!("set-env {} {:?}", name, value)
+    }
+    fn to_clap_shell(&self) -> anyhow::Result<clap_complete::Shell> {
+        Ok(clap_complete::Shell::Elvish)
+    }
+    fn use_on_cd(&self, _config: &crate::config::MamimiConfig) -> String {
+        hook::render(indoc!(
+            r#"
+                fn {hook} {
+                    try { {command} } catch { }
+                }
+                set after-chdir = [$@after-chdir {|_| {hook} }]
+                {hook}
+            "#
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syntax() {
+        let shell = Elvish;
+        assert_eq!(
+            shell.path(Path::new("/tmp/mamimi/bin")).unwrap(),
+            r#"set paths = ["/tmp/mamimi/bin" $@paths]"#
+        );
+        assert_eq!(
+            shell.set_env_var("MAMIMI_DIR", "/tmp/mamimi"),
+            r#"set-env MAMIMI_DIR "/tmp/mamimi""#
+        );
+        let script = shell.use_on_cd(&crate::config::MamimiConfig::default());
+        assert!(script.contains("set after-chdir = [$@after-chdir {|_| _mamimi_autoload_hook }]"));
+    }
+}
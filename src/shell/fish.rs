@@ -1,3 +1,4 @@
+use crate::shell::hook;
 use crate::shell::Shell;
 use anyhow::Ok;
 use indoc::indoc;
@@ -16,18 +17,18 @@ impl Shell for Fish {
     fn rehash(&self) -> Option<String> {
         Some("rehash".to_string())
     }
-    fn to_clap_shell(&self) -> clap_complete::Shell {
-        clap_complete::Shell::Fish
+    fn to_clap_shell(&self) -> anyhow::Result<clap_complete::Shell> {
+        Ok(clap_complete::Shell::Fish)
     }
-    fn use_on_cd(&self, config: &crate::config::MamimiConfig) -> String {
-        indoc!(
+    fn use_on_cd(&self, _config: &crate::config::MamimiConfig) -> String {
+        hook::render(indoc!(
             r#"
-                function _mamimi_autoload_hook --on-variable PWD --description 'Change Python version on directory change'
+                function {hook} --on-variable PWD --description 'Change Python version on directory change'
                     status --is-command-substitution; and return
-                    mamimi --log-level quiet local
+                    {command}
                 end
+                {hook}
             "#
-        )
-        .into()
+        ))
     }
 }
@@ -0,0 +1,39 @@
+/// The function every shell registers to run on directory change.
+pub const HOOK_FUNCTION: &str = "_mamimi_autoload_hook";
+
+/// The command the hook runs to pick up the version of the new directory.
+pub const HOOK_COMMAND: &str = "mamimi --log-level quiet local";
+
+/// Fills `{hook}` and `{command}` in a shell-specific `use_on_cd` template,
+/// so every shell registers the same hook and runs the same command.
+pub fn render(template: &str) -> String {
+    template
+        .replace("{hook}", HOOK_FUNCTION)
+        .replace("{command}", HOOK_COMMAND)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MamimiConfig;
+    use crate::shell::{Bash, Fish, PowerShell, Shell, Zsh};
+
+    #[test]
+    fn test_hooks_register_the_function_they_define() {
+        let config = MamimiConfig::default();
+        let shells: Vec<Box<dyn Shell>> = vec![
+            Box::from(Bash),
+            Box::from(Zsh),
+            Box::from(Fish),
+            Box::from(PowerShell),
+        ];
+        for shell in shells {
+            let script = shell.use_on_cd(&config);
+            assert!(!script.contains("{hook}"), "{:?}", shell);
+            assert!(!script.contains("{command}"), "{:?}", shell);
+            assert!(script.contains(HOOK_COMMAND), "{:?}", shell);
+            assert!(script.matches(HOOK_FUNCTION).count() >= 2, "{:?}", shell);
+            assert!(!script.contains("frum"), "{:?}", shell);
+        }
+    }
+}
@@ -2,19 +2,63 @@ mod unix;
 mod windows;
 
 #[cfg(unix)]
-pub use self::unix::infer_shell;
+use self::unix::infer_shell_from_process_tree;
 #[cfg(windows)]
-pub use self::windows::infer_shell;
+use self::windows::infer_shell_from_process_tree;
+
+/// Infers the shell from `MAMIMI_SHELL` when set, then from the process tree.
+pub fn infer_shell() -> Option<Box<dyn super::Shell>> {
+    shell_from_env().or_else(infer_shell_from_process_tree)
+}
+
+fn shell_from_env() -> Option<Box<dyn super::Shell>> {
+    let shell = std::env::var("MAMIMI_SHELL").ok()?;
+    let binary = std::path::Path::new(shell.trim())
+        .file_stem()?
+        .to_str()?
+        .to_lowercase();
+    binary
+        .parse::<Box<dyn super::Shell>>()
+        .ok()
+        .or_else(|| shell_from_string(&binary))
+        .or_else(|| {
+            log::warn!("MAMIMI_SHELL={:?} is not a supported shell", shell);
+            None
+        })
+}
 
 pub(self) fn shell_from_string(shell: &str) -> Option<Box<dyn super::Shell>> {
-    use super::{Bash, Fish, PowerShell, WindowsCommand, Zsh};
+    use super::{Bash, Elvish, Fish, Nushell, PowerShell, Tcsh, WindowsCommand, Xonsh, Zsh};
     match shell {
         "sh" | "bash" => return Some(Box::from(Bash)),
         "zsh" => return Some(Box::from(Zsh)),
         "fish" => return Some(Box::from(Fish)),
         "pwsh" => return Some(Box::from(PowerShell)),
         "cmd" => return Some(Box::from(WindowsCommand)),
+        "nu" => return Some(Box::from(Nushell)),
+        "elvish" => return Some(Box::from(Elvish)),
+        "xonsh" => return Some(Box::from(Xonsh)),
+        "tcsh" => return Some(Box::from(Tcsh)),
         cmd_name => log::debug!("binary is not a supported shell: {:?}", cmd_name),
     };
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::shell_from_string;
+
+    #[test]
+    fn test_shell_from_string() {
+        for (binary, shell) in [
+            ("nu", "Nushell"),
+            ("elvish", "Elvish"),
+            ("xonsh", "Xonsh"),
+            ("tcsh", "Tcsh"),
+        ] {
+            let inferred = shell_from_string(binary).map(|x| format!("{:?}", x));
+            assert_eq!(inferred.as_deref(), Some(shell));
+        }
+        assert!(shell_from_string("python3").is_none());
+    }
+}
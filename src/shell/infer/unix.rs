@@ -3,7 +3,6 @@
 use crate::shell::Shell;
 use log::debug;
 use std::io::{Error, ErrorKind};
-use thiserror::Error;
 
 #[derive(Debug)]
 struct ProcessInfo {
@@ -13,22 +12,27 @@ struct ProcessInfo {
 
 const MAX_INTERACTIONS: u8 = 10;
 
-pub fn infer_shell() -> Option<Box<dyn Shell>> {
+/// Processes that own a terminal session. The shell that started mamimi is
+/// never above them, so walking further would only find unrelated processes.
+const SESSION_ROOTS: &[&str] = &[
+    "tmux", "screen", "sshd", "login", "init", "systemd", "launchd",
+];
+
+pub fn infer_shell_from_process_tree() -> Option<Box<dyn Shell>> {
     let mut pid = Some(std::process::id());
     let mut visited = 0;
 
     while pid != None && visited < MAX_INTERACTIONS {
         let process_info = get_process_info(pid.unwrap()).ok()?;
-        let binary = process_info
-            .command
-            .trim_start_matches('-')
-            .split('/')
-            .last()
-            .expect("Can't read file name of process tree");
+        let binary = binary_name(&process_info.command);
 
         if let Some(shell) = super::shell_from_string(binary) {
             return Some(shell);
         }
+        if is_session_root(binary) {
+            debug!("reached the session root {:?}", binary);
+            return None;
+        }
 
         pid = process_info.parent_pid;
         visited += 1;
@@ -36,7 +40,64 @@ pub fn infer_shell() -> Option<Box<dyn Shell>> {
     None
 }
 
+/// Strips the directory and the `-` that marks login shells, e.g. `-/bin/zsh`.
+fn binary_name(command: &str) -> &str {
+    command
+        .trim_start_matches('-')
+        .split('/')
+        .last()
+        .expect("Can't read file name of process tree")
+}
+
+/// `tmux` renames its server process to `tmux: server`, so match on the prefix.
+fn is_session_root(binary: &str) -> bool {
+    SESSION_ROOTS.iter().any(|root| {
+        binary == *root
+            || binary
+                .strip_prefix(root)
+                .map_or(false, |rest| rest.starts_with(':'))
+    })
+}
+
+#[cfg(target_os = "linux")]
 fn get_process_info(pid: u32) -> std::io::Result<ProcessInfo> {
+    match get_process_info_from_proc(pid) {
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            debug!("/proc is not available, falling back to ps: {}", err);
+            get_process_info_from_ps(pid)
+        }
+        result => result,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_process_info(pid: u32) -> std::io::Result<ProcessInfo> {
+    get_process_info_from_ps(pid)
+}
+
+#[cfg(target_os = "linux")]
+fn get_process_info_from_proc(pid: u32) -> std::io::Result<ProcessInfo> {
+    let proc_dir = std::path::Path::new("/proc").join(pid.to_string());
+    let stat = std::fs::read_to_string(proc_dir.join("stat"))?;
+    let command = std::fs::read_to_string(proc_dir.join("comm"))?;
+
+    Ok(ProcessInfo {
+        parent_pid: parse_parent_pid_from_stat(&stat),
+        command: command.trim_end().into(),
+    })
+}
+
+/// `/proc/<pid>/stat` looks like `pid (comm) state ppid ...`. The command may
+/// contain spaces and parentheses, so the fields are read after the last `)`.
+#[cfg(any(target_os = "linux", test))]
+fn parse_parent_pid_from_stat(stat: &str) -> Option<u32> {
+    let (_, fields) = stat.rsplit_once(')')?;
+    let mut fields = fields.split_whitespace();
+    let _state = fields.next()?;
+    fields.next()?.parse().ok()
+}
+
+fn get_process_info_from_ps(pid: u32) -> std::io::Result<ProcessInfo> {
     use std::io::{BufRead, BufReader};
     use std::process::Command;
 
@@ -75,7 +136,7 @@ fn get_process_info(pid: u32) -> std::io::Result<ProcessInfo> {
 
 #[cfg(all(test, unix))]
 mod tests {
-    use super::get_process_info;
+    use super::*;
     use std::process::{Command, Stdio};
 
     #[test]
@@ -86,8 +147,40 @@ mod tests {
             .stderr(Stdio::piped())
             .spawn()
             .expect("Can't execute command");
-        let process_info = get_process_info(subprocess.id());
-        let parent_pid = process_info.ok().and_then(|x| x.parent_pid);
-        assert_eq!(parent_pid, Some(std::process::id()));
+        let process_info = get_process_info(subprocess.id()).unwrap();
+        assert_eq!(process_info.parent_pid, Some(std::process::id()));
+        assert_eq!(process_info.command, "bash");
+    }
+
+    #[test]
+    fn test_parse_parent_pid_from_stat() {
+        assert_eq!(
+            parse_parent_pid_from_stat("5279 (cat) R 5275 5279 5275 0 -1"),
+            Some(5275)
+        );
+        assert_eq!(
+            parse_parent_pid_from_stat("42 (tmux: server) S 1 42 42 0 -1"),
+            Some(1)
+        );
+        assert_eq!(
+            parse_parent_pid_from_stat("7 (a) (b) S 3 7 7 0 -1"),
+            Some(3)
+        );
+        assert_eq!(parse_parent_pid_from_stat("garbage"), None);
+    }
+
+    #[test]
+    fn test_binary_name() {
+        assert_eq!(binary_name("-zsh"), "zsh");
+        assert_eq!(binary_name("-/bin/bash"), "bash");
+        assert_eq!(binary_name("/usr/local/bin/fish"), "fish");
+    }
+
+    #[test]
+    fn test_is_session_root() {
+        assert!(is_session_root("tmux: server"));
+        assert!(is_session_root("sshd"));
+        assert!(!is_session_root("sudo"));
+        assert!(!is_session_root("tmuxinator"));
     }
 }
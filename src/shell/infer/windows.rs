@@ -5,7 +5,7 @@ use log::debug;
 use std::ffi::OsStr;
 use sysinfo::{ProcessExt, System, SystemExt};
 
-pub fn infer_shell() -> Option<Box<dyn Shell>> {
+pub fn infer_shell_from_process_tree() -> Option<Box<dyn Shell>> {
     let mut system = System::new();
     let mut current_pid = sysinfo::get_current_pid().ok();
 
@@ -1,17 +1,26 @@
 pub mod bash;
+pub mod elvish;
 pub mod fish;
+pub mod hook;
 pub mod infer;
+pub mod nushell;
 pub mod powershell;
+pub mod tcsh;
 pub mod windows_command;
+pub mod xonsh;
 pub mod zsh;
 
 #[allow(clippy::module_inception)]
 mod shell;
 
 pub use bash::Bash;
+pub use elvish::Elvish;
 pub use fish::Fish;
 pub use infer::infer_shell;
+pub use nushell::Nushell;
 pub use powershell::PowerShell;
 pub use shell::{Shell, AVAILABLE_SHELLS};
+pub use tcsh::Tcsh;
 pub use windows_command::WindowsCommand;
+pub use xonsh::Xonsh;
 pub use zsh::Zsh;
@@ -0,0 +1,62 @@
+use crate::shell::hook;
+use crate::shell::Shell;
+use anyhow::Ok;
+use indoc::indoc;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct Nushell;
+
+impl Shell for Nushell {
+    fn path(&self, path: &Path) -> anyhow::Result<String> {
+        Ok(format!(
+            "$env.PATH = ($env.PATH | prepend {:?})",
+            path.to_str().unwrap()
+        ))
+    }
+    fn set_env_var(&self, name: &str, value: &str) -> String {
+        format!("$env.{} = {:?}", name, value)
+    }
+    fn to_clap_shell(&self) -> anyhow::Result<clap_complete::Shell> {
+        anyhow::bail!("Shell completion is not supported for Nushell yet.")
+    }
+    fn use_on_cd(&self, _config: &crate::config::MamimiConfig) -> String {
+        hook::render(indoc!(
+            r#"
+                def --env {hook} [] {
+                    do --ignore-errors { ^{command} }
+                }
+                $env.config = ($env.config | upsert hooks.env_change.PWD {|config|
+                    let hooks = ($config | get -i hooks.env_change.PWD | default [])
+                    $hooks | append {|before, after| {hook} }
+                })
+                {hook}
+            "#
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syntax() {
+        let shell = Nushell;
+        assert_eq!(
+            shell.path(Path::new("/tmp/mamimi/bin")).unwrap(),
+            r#"$env.PATH = ($env.PATH | prepend "/tmp/mamimi/bin")"#
+        );
+        assert_eq!(
+            shell.set_env_var("MAMIMI_DIR", "/tmp/mamimi"),
+            r#"$env.MAMIMI_DIR = "/tmp/mamimi""#
+        );
+        let script = shell.use_on_cd(&crate::config::MamimiConfig::default());
+        assert!(script.contains("hooks.env_change.PWD"));
+        assert!(script.contains("^mamimi --log-level quiet local"));
+        assert_eq!(
+            script.trim_end().lines().last(),
+            Some("_mamimi_autoload_hook")
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::shell::hook;
 use crate::shell::Shell;
 use anyhow::Ok;
 use indoc::indoc;
@@ -17,22 +18,23 @@ impl Shell for PowerShell {
     fn set_env_var(&self, name: &str, value: &str) -> String {
         format!(r#"$env:{} = "{}""#, name, value)
     }
-    fn to_clap_shell(&self) -> clap_complete::Shell {
-        clap_complete::Shell::PowerShell
+    fn to_clap_shell(&self) -> anyhow::Result<clap_complete::Shell> {
+        Ok(clap_complete::Shell::PowerShell)
     }
-    fn use_on_cd(&self, config: &crate::config::MamimiConfig) -> String {
-        indoc!(
+    fn use_on_cd(&self, _config: &crate::config::MamimiConfig) -> String {
+        hook::render(indoc!(
             r#"
-            function Set-LocationWithMamimi {
-                param($path)
-                Set-Location $path
-                If (Test-Path .python-version) { & mamimi --log-level quiet local }
-            }
-            Set-Alias cd_with_frum Set-LocationWithMamimi -Force
-            Remove-Item alias:\cd
-            New-Alias cd Set-LocationWithMamimi
-        "#
-        )
-        .into()
+                function global:{hook} {
+                    & {command}
+                }
+                function global:Set-LocationWithMamimi {
+                    param($path)
+                    if ($null -eq $path) { Set-Location } else { Set-Location $path }
+                    {hook}
+                }
+                Set-Alias -Name cd -Value Set-LocationWithMamimi -Option AllScope -Scope Global -Force
+                {hook}
+            "#
+        ))
     }
 }
@@ -7,15 +7,33 @@ pub trait Shell: Debug {
     fn rehash(&self) -> Option<String> {
         None
     }
-    fn to_clap_shell(&self) -> clap_complete::Shell;
+    fn to_clap_shell(&self) -> anyhow::Result<clap_complete::Shell>;
     fn use_on_cd(&self, config: &crate::config::MamimiConfig) -> String;
 }
 
 #[cfg(windows)]
-pub const AVAILABLE_SHELLS: &[&str; 5] = &["cmd", "powershell", "bash", "fish", "zsh"];
+pub const AVAILABLE_SHELLS: &[&str; 8] = &[
+    "cmd",
+    "powershell",
+    "bash",
+    "fish",
+    "zsh",
+    "nushell",
+    "elvish",
+    "xonsh",
+];
 
 #[cfg(unix)]
-pub const AVAILABLE_SHELLS: &[&str; 4] = &["bash", "fish", "zsh", "powershell"];
+pub const AVAILABLE_SHELLS: &[&str; 8] = &[
+    "bash",
+    "fish",
+    "zsh",
+    "powershell",
+    "nushell",
+    "elvish",
+    "xonsh",
+    "tcsh",
+];
 
 impl std::str::FromStr for Box<dyn Shell> {
     type Err = String;
@@ -27,13 +45,11 @@ impl std::str::FromStr for Box<dyn Shell> {
             "bash" => Ok(Box::from(super::bash::Bash)),
             "fish" => Ok(Box::from(super::fish::Fish)),
             "powershell" => Ok(Box::from(super::powershell::PowerShell)),
+            "nushell" | "nu" => Ok(Box::from(super::nushell::Nushell)),
+            "elvish" => Ok(Box::from(super::elvish::Elvish)),
+            "xonsh" => Ok(Box::from(super::xonsh::Xonsh)),
+            "tcsh" => Ok(Box::from(super::tcsh::Tcsh)),
             shell_type => Err(format!("I don't know the shell type of {:?}", shell_type)),
         }
     }
 }
-
-impl From<Box<dyn Shell>> for clap_complete::Shell {
-    fn from(shell: Box<dyn Shell>) -> Self {
-        shell.to_clap_shell()
-    }
-}
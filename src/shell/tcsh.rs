@@ -0,0 +1,49 @@
+use crate::shell::hook;
+use crate::shell::Shell;
+use anyhow::Ok;
+use std::path::Path;
+
+/// Every line ends with `;` since tcsh users run ``eval "`mamimi init`"``,
+/// which joins the output into a single line.
+#[derive(Debug)]
+pub struct Tcsh;
+
+impl Shell for Tcsh {
+    fn path(&self, path: &Path) -> anyhow::Result<String> {
+        Ok(format!("setenv PATH {:?}:$PATH;", path.to_str().unwrap()))
+    }
+    fn set_env_var(&self, name: &str, value: &str) -> String {
+        format!("setenv {} {:?};", name, value)
+    }
+    fn rehash(&self) -> Option<String> {
+        Some("rehash;".to_string())
+    }
+    fn to_clap_shell(&self) -> anyhow::Result<clap_complete::Shell> {
+        anyhow::bail!("Shell completion is not supported for tcsh yet.")
+    }
+    fn use_on_cd(&self, _config: &crate::config::MamimiConfig) -> String {
+        format!("alias cwdcmd '{}';", hook::HOOK_COMMAND)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syntax() {
+        let shell = Tcsh;
+        assert_eq!(
+            shell.path(Path::new("/tmp/mamimi/bin")).unwrap(),
+            r#"setenv PATH "/tmp/mamimi/bin":$PATH;"#
+        );
+        assert_eq!(
+            shell.set_env_var("MAMIMI_DIR", "/tmp/mamimi"),
+            r#"setenv MAMIMI_DIR "/tmp/mamimi";"#
+        );
+        assert_eq!(
+            shell.use_on_cd(&crate::config::MamimiConfig::default()),
+            "alias cwdcmd 'mamimi --log-level quiet local';"
+        );
+    }
+}
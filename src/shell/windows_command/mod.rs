@@ -5,8 +5,8 @@ use std::path::Path;
 pub struct WindowsCommand;
 
 impl Shell for WindowsCommand {
-    fn to_clap_shell(&self) -> clap_complete::Shell {
-        panic!("Shell completion is not supported for Windows Command Promt. Could you try to use PowerShell for a better experience?");
+    fn to_clap_shell(&self) -> anyhow::Result<clap_complete::Shell> {
+        anyhow::bail!("Shell completion is not supported for Windows Command Prompt. Could you try to use PowerShell for a better experience?")
     }
     fn path(&self, path: &Path) -> anyhow::Result<String> {
         let current_path = std::env::var_os("path").expect("Can't read Path env var");
@@ -0,0 +1,51 @@
+use crate::shell::hook;
+use crate::shell::Shell;
+use anyhow::Ok;
+use indoc::indoc;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct Xonsh;
+
+impl Shell for Xonsh {
+    fn path(&self, path: &Path) -> anyhow::Result<String> {
+        Ok(format!("$PATH.insert(0, {:?})", path.to_str().unwrap()))
+    }
+    fn set_env_var(&self, name: &str, value: &str) -> String {
+        format!("${} = {:?}", name, value)
+    }
+    fn to_clap_shell(&self) -> anyhow::Result<clap_complete::Shell> {
+        anyhow::bail!("Shell completion is not supported for Xonsh yet.")
+    }
+    fn use_on_cd(&self, _config: &crate::config::MamimiConfig) -> String {
+        hook::render(indoc!(
+            r#"
+                @events.on_chdir
+                def {hook}(olddir, newdir, **kwargs):
+                    ![{command}]
+                {hook}(None, $PWD)
+            "#
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syntax() {
+        let shell = Xonsh;
+        assert_eq!(
+            shell.path(Path::new("/tmp/mamimi/bin")).unwrap(),
+            r#"$PATH.insert(0, "/tmp/mamimi/bin")"#
+        );
+        assert_eq!(
+            shell.set_env_var("MAMIMI_DIR", "/tmp/mamimi"),
+            r#"$MAMIMI_DIR = "/tmp/mamimi""#
+        );
+        let script = shell.use_on_cd(&crate::config::MamimiConfig::default());
+        assert!(script.starts_with("@events.on_chdir\n"));
+        assert!(script.contains("![mamimi --log-level quiet local]"));
+    }
+}
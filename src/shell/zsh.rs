@@ -1,3 +1,4 @@
+use crate::shell::hook;
 use crate::shell::Shell;
 use anyhow::Ok;
 use indoc::indoc;
@@ -16,20 +17,19 @@ impl Shell for Zsh {
     fn rehash(&self) -> Option<String> {
         Some("rehash".to_string())
     }
-    fn to_clap_shell(&self) -> clap_complete::Shell {
-        clap_complete::Shell::Zsh
+    fn to_clap_shell(&self) -> anyhow::Result<clap_complete::Shell> {
+        Ok(clap_complete::Shell::Zsh)
     }
-    fn use_on_cd(&self, config: &crate::config::MamimiConfig) -> String {
-        indoc!(
+    fn use_on_cd(&self, _config: &crate::config::MamimiConfig) -> String {
+        hook::render(indoc!(
             r#"
                 autoload -U add-zsh-hook
-                _mamimi_autoload_hook () {
-                    mamimi --log-level quiet local
+                {hook} () {
+                    {command}
                 }
-                add-zsh-hook chpwd _frum_autoload_hook \
-                    && _mamimi_autoload_hook
+                add-zsh-hook chpwd {hook} \
+                    && {hook}
             "#
-        )
-        .into()
+        ))
     }
 }
@@ -0,0 +1,178 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Extension modules CPython quietly skips when their headers are missing.
+pub const OPTIONAL_MODULES: &[&str] = &[
+    "_ssl", "_hashlib", "zlib", "_ctypes", "_sqlite3", "_lzma", "_bz2", "readline", "_curses",
+    "_tkinter", "_uuid", "_dbm",
+];
+
+/// Headings CPython prints after `make` above the modules it could not build,
+/// both from `setup.py` (<= 3.11) and `check_extension_modules.py` (>= 3.12).
+const MISSING_HEADINGS: &[&str] = &[
+    "The necessary bits to build these optional modules were not found:",
+    "Failed to build these modules:",
+    "Following modules built successfully but were removed because they could not be imported:",
+    "Could not build the ssl module!",
+];
+
+/// Collects the module names listed under the "missing modules" headings of a build log.
+pub fn parse_missing_modules(build_output: &str) -> BTreeSet<String> {
+    let mut missing = BTreeSet::new();
+    let mut in_section = false;
+    for line in build_output.lines() {
+        let line = line.trim();
+        if MISSING_HEADINGS
+            .iter()
+            .any(|heading| line.starts_with(heading))
+        {
+            in_section = true;
+            if line.starts_with("Could not build the ssl module!") {
+                missing.insert("_ssl".to_string());
+            }
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if line.is_empty() || line.starts_with("To find the necessary bits") {
+            in_section = false;
+            continue;
+        }
+        if line.contains(' ') && !line.split_whitespace().all(is_module_name) {
+            in_section = false;
+            continue;
+        }
+        missing.extend(line.split_whitespace().map(ToString::to_string));
+    }
+    missing
+}
+
+fn is_module_name(word: &str) -> bool {
+    word.chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// The interpreter `make` leaves in the source tree, `python.exe` on case-insensitive file systems.
+pub fn built_interpreter(source_dir: &Path) -> Option<PathBuf> {
+    ["python", "python.exe"]
+        .iter()
+        .map(|name| source_dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Imports every module with `python` and returns the ones that fail.
+pub fn import_test(python: &Path, modules: &[&str]) -> std::io::Result<BTreeSet<String>> {
+    let script = format!(
+        "import importlib\nfor name in {:?}:\n    try:\n        importlib.import_module(name)\n    except Exception:\n        print(name)\n",
+        modules
+    );
+    let output = Command::new(python)
+        .arg("-c")
+        .arg(script)
+        .current_dir(python.parent().unwrap_or_else(|| Path::new(".")))
+        .output()?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+#[derive(Debug)]
+pub struct ModuleReport {
+    pub missing: BTreeSet<String>,
+}
+
+impl ModuleReport {
+    pub fn new(build_output: &str, interpreter: Option<&Path>) -> Self {
+        let mut missing = parse_missing_modules(build_output);
+        if let Some(python) = interpreter {
+            match import_test(python, OPTIONAL_MODULES) {
+                Ok(failed) => missing.extend(failed),
+                Err(err) => log::debug!("Can't import-test the built interpreter: {}", err),
+            }
+        }
+        Self { missing }
+    }
+
+    /// The modules of `required` that are missing.
+    pub fn missing_required<'a>(&self, required: &'a [String]) -> Vec<&'a String> {
+        required
+            .iter()
+            .filter(|module| self.missing.contains(module.as_str()))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for ModuleReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let others = self
+            .missing
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !OPTIONAL_MODULES.contains(name));
+        let names = OPTIONAL_MODULES
+            .iter()
+            .copied()
+            .chain(others)
+            .collect::<Vec<_>>();
+        let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+        for name in names {
+            let status = if self.missing.contains(name) {
+                "missing"
+            } else {
+                "ok"
+            };
+            writeln!(f, "    {:width$}  {}", name, status, width = width)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_setup_py_summary() {
+        let output = "\
+            Python build finished successfully!\n\
+            The necessary bits to build these optional modules were not found:\n\
+            _bz2                  _lzma                 _tkinter\n\
+            readline\n\
+            To find the necessary bits, look in setup.py in detect_modules() for the module's name.\n\
+            \n\
+            Failed to build these modules:\n\
+            _ctypes\n\
+            \n";
+        let missing = parse_missing_modules(output);
+        assert_eq!(
+            missing.into_iter().collect::<Vec<_>>(),
+            vec!["_bz2", "_ctypes", "_lzma", "_tkinter", "readline"]
+        );
+    }
+
+    #[test]
+    fn test_parse_ignores_unrelated_lines() {
+        let output = "gcc -c -o Modules/main.o Modules/main.c\nmake: Nothing to be done\n";
+        assert!(parse_missing_modules(output).is_empty());
+    }
+
+    #[test]
+    fn test_missing_required() {
+        let report = ModuleReport {
+            missing: ["_ssl", "_tkinter"]
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        };
+        let required = vec!["_ssl".to_string(), "zlib".to_string()];
+        assert_eq!(
+            report.missing_required(&required),
+            vec![&"_ssl".to_string()]
+        );
+        assert!(report.to_string().contains("_tkinter  missing"));
+    }
+}
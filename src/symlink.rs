@@ -12,9 +12,21 @@ pub fn create_symlink_dir<P: AsRef<Path>, U: AsRef<Path>>(from: P, to: U) -> std
     Ok(())
 }
 
+#[cfg(unix)]
+pub fn create_symlink_file<P: AsRef<Path>, U: AsRef<Path>>(from: P, to: U) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(from, to)?;
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn create_symlink_file<P: AsRef<Path>, U: AsRef<Path>>(from: P, to: U) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(from, to)?;
+    Ok(())
+}
+
 #[cfg(unix)]
 pub fn remove_symlink_dir<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
-    std::fs::remove_dir(path)?;
+    std::fs::remove_file(path)?;
     Ok(())
 }
 
@@ -1,6 +1,13 @@
-use std::path::PathBuf;
+use crate::config::MamimiConfig;
+use crate::interpreter::probe_version;
+use crate::python_version::PythonVersion;
+use crate::symlink::create_symlink_file;
+use log::debug;
+use std::path::{Path, PathBuf};
 
-pub fn path() -> PathBuf {
+/// Where aliases to `system` pointed before system interpreters were
+/// discovered. Such aliases are still recognized.
+pub fn legacy_path() -> PathBuf {
     let path_as_string = if cfg!(windows) {
         "Z:/_mamimi/Nothing/Should/Be/Here/installation"
     } else {
@@ -13,3 +20,147 @@ pub fn path() -> PathBuf {
 pub fn display_name() -> &'static str {
     "system"
 }
+
+/// The installation `system` resolves to: a directory of links to the
+/// interpreters found on `PATH`, laid out like an installed version.
+pub fn path(config: &MamimiConfig) -> PathBuf {
+    config.base_dir_with_default().join(display_name())
+}
+
+fn bin_dir(installation: &Path) -> PathBuf {
+    if cfg!(windows) {
+        installation.to_path_buf()
+    } else {
+        installation.join("bin")
+    }
+}
+
+/// An interpreter on `PATH` that mamimi didn't install.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemInterpreter {
+    /// The names it is reachable under, e.g. `python3` and `python3.11`.
+    pub names: Vec<String>,
+    pub path: PathBuf,
+    pub version: PythonVersion,
+}
+
+/// Whether `name` is `python3` or `python3.X`.
+fn is_interpreter_name(name: &str) -> bool {
+    let name = name.strip_suffix(".exe").unwrap_or(name);
+    match name.strip_prefix("python3") {
+        Some("") => true,
+        Some(minor) => minor.strip_prefix('.').map_or(false, |minor| {
+            !minor.is_empty() && minor.chars().all(|c| c.is_ascii_digit())
+        }),
+        None => false,
+    }
+}
+
+/// The first executable called `python3` or `python3.X` on `PATH`, skipping
+/// mamimi's own directories, in the order the shell would find them.
+fn find_on_path(config: &MamimiConfig) -> Vec<(String, PathBuf)> {
+    let own = config.base_dir_with_default().canonicalize().ok();
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut found: Vec<(String, PathBuf)> = vec![];
+    for dir in std::env::split_paths(&path) {
+        // Multishell directories resolve into the base directory as well
+        let dir = match dir.canonicalize() {
+            Ok(dir) if own.as_ref().map_or(true, |own| !dir.starts_with(own)) => dir,
+            _ => continue,
+        };
+        let mut entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries
+                .filter_map(Result::ok)
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| is_interpreter_name(name))
+                .collect::<Vec<_>>(),
+            Err(_) => continue,
+        };
+        entries.sort();
+        for name in entries {
+            let candidate = dir.join(&name);
+            if crate::executable::is_executable(&candidate)
+                && !found.iter().any(|(found, _)| *found == name)
+            {
+                found.push((name, candidate));
+            }
+        }
+    }
+    found
+}
+
+/// Whether `PATH` has an interpreter `system` can resolve to, without running it.
+pub fn is_available(config: &MamimiConfig) -> bool {
+    !find_on_path(config).is_empty()
+}
+
+/// The interpreters on `PATH`, the one `python3` runs first.
+pub fn discover(config: &MamimiConfig) -> Vec<SystemInterpreter> {
+    let mut found = find_on_path(config);
+    found.sort_by_key(|(name, _)| name.strip_suffix(".exe").unwrap_or(name) != "python3");
+    let mut interpreters: Vec<SystemInterpreter> = vec![];
+    for (name, path) in found {
+        let target = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if let Some(interpreter) = interpreters
+            .iter_mut()
+            .find(|interpreter| interpreter.path.canonicalize().ok().as_ref() == Some(&target))
+        {
+            interpreter.names.push(name);
+            continue;
+        }
+        match probe_version(&path) {
+            Some(version) => interpreters.push(SystemInterpreter {
+                names: vec![name],
+                path,
+                version,
+            }),
+            None => debug!("Skipping {:?}, it didn't report a version", path),
+        }
+    }
+    interpreters
+}
+
+/// Points the `system` installation at the interpreters currently on `PATH`.
+/// Returns `None` when there are none.
+pub fn refresh(config: &MamimiConfig) -> std::io::Result<Option<PathBuf>> {
+    let found = find_on_path(config);
+    let main = match found
+        .iter()
+        .find(|(name, _)| name.strip_suffix(".exe").unwrap_or(name) == "python3")
+        .or_else(|| found.first())
+    {
+        Some((_, main)) => main.clone(),
+        None => return Ok(None),
+    };
+    let installation = path(config);
+    if installation.exists() {
+        std::fs::remove_dir_all(&installation)?;
+    }
+    let bin = bin_dir(&installation);
+    std::fs::create_dir_all(&bin)?;
+    let python = if cfg!(windows) {
+        "python.exe"
+    } else {
+        "python"
+    };
+    create_symlink_file(&main, bin.join(python))?;
+    for (name, path) in &found {
+        create_symlink_file(path, bin.join(name))?;
+    }
+    Ok(Some(installation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_interpreter_name() {
+        assert!(is_interpreter_name("python3"));
+        assert!(is_interpreter_name("python3.11"));
+        assert!(is_interpreter_name("python3.11.exe"));
+        assert!(!is_interpreter_name("python3.11-config"));
+        assert!(!is_interpreter_name("python3."));
+        assert!(!is_interpreter_name("python"));
+    }
+}
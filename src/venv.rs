@@ -0,0 +1,95 @@
+use log::debug;
+use std::path::{Path, PathBuf};
+
+/// Directories that never contain projects worth searching.
+const SKIPPED_DIRS: [&str; 3] = [".git", "node_modules", "__pycache__"];
+
+/// A virtual environment, found by its `pyvenv.cfg`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Venv {
+    pub path: PathBuf,
+    /// The `bin` directory of the interpreter the venv was created from.
+    pub home: PathBuf,
+}
+
+impl Venv {
+    pub fn read(path: &Path) -> Option<Self> {
+        let cfg = std::fs::read_to_string(path.join("pyvenv.cfg")).ok()?;
+        let home = cfg.lines().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "home").then(|| PathBuf::from(value.trim()))
+        })?;
+        Some(Self {
+            path: path.to_path_buf(),
+            home,
+        })
+    }
+
+    /// Whether the venv was created from the interpreter installed in `installation_dir`.
+    pub fn uses(&self, installation_dir: &Path) -> bool {
+        let canonical = |path: &Path| std::fs::canonicalize(path).unwrap_or(path.to_path_buf());
+        canonical(&self.home).starts_with(canonical(installation_dir))
+    }
+}
+
+/// Every venv in `root` and its subdirectories, including hidden ones such as `.venv`.
+pub fn find_venvs(root: &Path) -> Vec<Venv> {
+    let mut venvs = vec![];
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        if let Some(venv) = Venv::read(&dir) {
+            venvs.push(venv);
+            continue;
+        }
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                debug!("Skipping {:?}: {}", dir, err);
+                continue;
+            }
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let is_dir = entry.file_type().map_or(false, |x| x.is_dir());
+            let name = entry.file_name();
+            if is_dir && !SKIPPED_DIRS.iter().any(|skipped| name == *skipped) {
+                dirs.push(entry.path());
+            }
+        }
+    }
+    venvs.sort_by(|a, b| a.path.cmp(&b.path));
+    venvs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_venvs() {
+        let root = tempfile::tempdir().unwrap();
+        let install = root.path().join("versions/3.11.6");
+        std::fs::create_dir_all(install.join("bin")).unwrap();
+        let venv = root.path().join("project/.venv");
+        std::fs::create_dir_all(venv.join("lib")).unwrap();
+        std::fs::write(
+            venv.join("pyvenv.cfg"),
+            format!(
+                "home = {}\ninclude-system-site-packages = false\nversion = 3.11.6\n",
+                install.join("bin").display()
+            ),
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.path().join("project/.git/.venv")).unwrap();
+        std::fs::write(
+            root.path().join("project/.git/.venv/pyvenv.cfg"),
+            "home = /",
+        )
+        .unwrap();
+
+        let venvs = find_venvs(root.path());
+        assert_eq!(venvs.len(), 1);
+        assert_eq!(venvs[0].path, venv);
+        assert!(venvs[0].uses(&install));
+        assert!(!venvs[0].uses(&root.path().join("versions/3.11.9")));
+    }
+}
@@ -2,10 +2,10 @@ use crate::input_version::InputVersion;
 use encoding_rs_io::DecodeReaderBytes;
 use log::{debug, info};
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-const VERSION_FILE: &str = ".python-version";
+pub const VERSION_FILE: &str = ".python-version";
 
 pub fn get_user_version_for_directory(path: PathBuf) -> Option<InputVersion> {
     let version_file_path = find_up(path, VERSION_FILE)?;
@@ -40,6 +40,35 @@ pub fn get_user_version_for_file(path: PathBuf) -> Option<InputVersion> {
     }
 }
 
+/// Every version file in `root` and its subdirectories, skipping hidden
+/// directories such as `.git` and symlinked ones.
+pub fn find_version_files(root: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut found = vec![];
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if dir != root => {
+                debug!("Skipping {:?}: {}", dir, err);
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let name = entry.file_name();
+            if name == VERSION_FILE && file_type.is_file() {
+                found.push(entry.path());
+            } else if file_type.is_dir() && !name.to_string_lossy().starts_with('.') {
+                dirs.push(entry.path());
+            }
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
 pub fn find_up(search_dir: PathBuf, file_name: &str) -> Option<PathBuf> {
     for dir in each_dir(search_dir) {
         let path = dir.join(&file_name);
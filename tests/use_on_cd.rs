@@ -0,0 +1,164 @@
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A mamimi base dir with two fake installations and a project pinned to one of them.
+struct Fixture {
+    root: tempfile::TempDir,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let root = tempfile::tempdir().expect("Can't create a temp directory");
+        let base_dir = root.path().join("mamimi");
+        for version in ["3.8.0", "3.9.6"] {
+            fake_python(&base_dir.join("versions").join(version), version);
+        }
+        let aliases_dir = base_dir.join("aliases");
+        std::fs::create_dir_all(&aliases_dir).unwrap();
+        std::os::unix::fs::symlink(
+            base_dir.join("versions").join("3.8.0"),
+            aliases_dir.join("default"),
+        )
+        .unwrap();
+
+        let project = root.path().join("project");
+        std::fs::create_dir_all(project.join("nested")).unwrap();
+        std::fs::write(project.join(".python-version"), "3.9.6\n").unwrap();
+        Self { root }
+    }
+
+    fn base_dir(&self) -> PathBuf {
+        self.root.path().join("mamimi")
+    }
+
+    fn run(&self, shell: &str, args: &[&str], script: &str) -> String {
+        let bin_dir = Path::new(env!("CARGO_BIN_EXE_mamimi")).parent().unwrap();
+        let path = std::env::join_paths(
+            std::iter::once(bin_dir.to_path_buf())
+                .chain(std::env::split_paths(&std::env::var_os("PATH").unwrap())),
+        )
+        .unwrap();
+        let output = Command::new(shell)
+            .args(args)
+            .arg(script)
+            .current_dir(self.root.path())
+            .env("PATH", path)
+            .env("MAMIMI_DIR", self.base_dir())
+            .env_remove("MAMIMI_MULTISHELL_PATH")
+            .output()
+            .expect("Can't run shell");
+        String::from_utf8(output.stdout).unwrap()
+    }
+}
+
+/// Writes a `python` script printing `version` into `dir/bin`.
+fn fake_python(dir: &Path, version: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    let bin = dir.join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    let python = bin.join("python");
+    std::fs::write(&python, format!("#!/bin/sh\necho {}\n", version)).unwrap();
+    std::fs::set_permissions(&python, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn has_shell(shell: &str) -> bool {
+    Command::new(shell)
+        .arg("-c")
+        .arg("exit 0")
+        .output()
+        .map(|x| x.status.success())
+        .unwrap_or(false)
+}
+
+const EXPECTED: &str = "3.8.0\n3.9.6\n3.9.6\n3.8.0\n";
+
+#[test]
+fn test_bash_use_on_cd() {
+    if !has_shell("bash") {
+        return;
+    }
+    let output = Fixture::new().run(
+        "bash",
+        &["-c"],
+        r#"
+            shopt -s expand_aliases
+            eval "$(mamimi init --shell bash --use-on-cd)"
+            python
+            cd project
+            python
+            cd nested
+            python
+            cd ../..
+            python
+        "#,
+    );
+    assert_eq!(output, EXPECTED);
+}
+
+#[test]
+fn test_zsh_use_on_cd() {
+    if !has_shell("zsh") {
+        return;
+    }
+    let output = Fixture::new().run(
+        "zsh",
+        &["-c"],
+        r#"
+            eval "$(mamimi init --shell zsh --use-on-cd)"
+            python
+            cd project
+            python
+            cd nested
+            python
+            cd ../..
+            python
+        "#,
+    );
+    assert_eq!(output, EXPECTED);
+}
+
+#[test]
+fn test_fish_use_on_cd() {
+    if !has_shell("fish") {
+        return;
+    }
+    let output = Fixture::new().run(
+        "fish",
+        &["-c"],
+        r#"
+            mamimi init --shell fish --use-on-cd | source
+            python
+            cd project
+            python
+            cd nested
+            python
+            cd ../..
+            python
+        "#,
+    );
+    assert_eq!(output, EXPECTED);
+}
+
+#[test]
+fn test_powershell_use_on_cd() {
+    if !has_shell("pwsh") {
+        return;
+    }
+    let output = Fixture::new().run(
+        "pwsh",
+        &["-NoProfile", "-NonInteractive", "-Command"],
+        r#"
+            mamimi init --shell powershell --use-on-cd | Out-String | Invoke-Expression
+            python
+            cd project
+            python
+            cd nested
+            python
+            cd ../..
+            python
+        "#,
+    );
+    assert_eq!(output, EXPECTED);
+}